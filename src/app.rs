@@ -0,0 +1,43 @@
+// Copyright 2019-2023 Tauri Programme within The Commons Conservancy
+// SPDX-License-Identifier: Apache-2.0
+// SPDX-License-Identifier: MIT
+
+//! Integration with a Tauri app, built with the `tauri` feature.
+//!
+//! Resolves snapshot paths under the data directory of the app, which follows the conventions
+//! of each platform, so that every `initialize` call finds the same file. Explicit paths
+//! remain supported: any [`SnapshotPath`] can still be passed instead.
+
+use std::{ffi::OsStr, fs, io, path::Path};
+
+use tauri::{AppHandle, Manager, Runtime};
+
+use crate::{
+    paths::SnapshotPath,
+    stronghold::{Error, Result},
+};
+
+/// File name of the snapshot returned by [`default_snapshot_path`].
+pub const DEFAULT_SNAPSHOT_FILE: &str = "vault.hold";
+
+/// [`DEFAULT_SNAPSHOT_FILE`] in the data directory of the app, see [`app_snapshot_path`].
+pub fn default_snapshot_path<R: Runtime>(app: &AppHandle<R>) -> Result<SnapshotPath> {
+    app_snapshot_path(app, DEFAULT_SNAPSHOT_FILE)
+}
+
+/// The snapshot `file_name` in the data directory of the app, e.g. to keep one snapshot per
+/// account. `file_name` must be a plain file name, not a path.
+///
+/// The directory is created if it doesn't exist yet, since saving a snapshot doesn't create
+/// it. Fails if the platform has no data directory for the app.
+pub fn app_snapshot_path<R: Runtime>(app: &AppHandle<R>, file_name: &str) -> Result<SnapshotPath> {
+    if Path::new(file_name).file_name() != Some(OsStr::new(file_name)) {
+        return Err(Error::invalid_input(
+            "file_name",
+            "must be a file name, without directories",
+        ));
+    }
+    let dir = app.path().app_data_dir().map_err(io::Error::other)?;
+    fs::create_dir_all(&dir)?;
+    Ok(dir.join(file_name).into())
+}
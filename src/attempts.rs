@@ -0,0 +1,87 @@
+// Copyright 2019-2023 Tauri Programme within The Commons Conservancy
+// SPDX-License-Identifier: Apache-2.0
+// SPDX-License-Identifier: MIT
+
+use std::{
+    collections::HashMap,
+    sync::Mutex,
+    time::{Duration, Instant},
+};
+
+use crate::{
+    paths::SnapshotPath,
+    stronghold::{Error, Result},
+};
+
+/// Limits how often a wrong password can be tried against a snapshot.
+#[derive(Clone, Copy, Debug)]
+pub struct AttemptLimit {
+    /// Failed attempts allowed before further attempts are delayed.
+    pub max_attempts: u32,
+    /// Delay after the first attempt over the limit. Doubles with each further failure.
+    pub base_delay: Duration,
+    /// Upper bound for the delay.
+    pub max_delay: Duration,
+}
+
+#[derive(Default)]
+struct Failures {
+    count: u32,
+    locked_until: Option<Instant>,
+}
+
+/// Per-snapshot failed password attempt counters.
+#[derive(Default)]
+pub(crate) struct Attempts {
+    limit: Option<AttemptLimit>,
+    failures: Mutex<HashMap<SnapshotPath, Failures>>,
+}
+
+impl Attempts {
+    pub(crate) fn new(limit: Option<AttemptLimit>) -> Self {
+        Self {
+            limit,
+            failures: Default::default(),
+        }
+    }
+
+    /// Fails with [`Error::TooManyAttempts`] while `path` is in a backoff period.
+    pub(crate) fn check(&self, path: &SnapshotPath) -> Result<()> {
+        if self.limit.is_none() {
+            return Ok(());
+        }
+        let failures = self.failures.lock().unwrap();
+        let locked_until = failures.get(path).and_then(|f| f.locked_until);
+        match locked_until {
+            Some(until) => match until.checked_duration_since(Instant::now()) {
+                Some(retry_after) if !retry_after.is_zero() => {
+                    Err(Error::TooManyAttempts { retry_after })
+                }
+                _ => Ok(()),
+            },
+            None => Ok(()),
+        }
+    }
+
+    /// Records the outcome of an unlock attempt. Success resets the counter.
+    pub(crate) fn record(&self, path: &SnapshotPath, success: bool) {
+        let Some(limit) = self.limit else {
+            return;
+        };
+        let mut failures = self.failures.lock().unwrap();
+        if success {
+            failures.remove(path);
+            return;
+        }
+        let entry = failures.entry(path.clone()).or_default();
+        entry.count = entry.count.saturating_add(1);
+        if entry.count >= limit.max_attempts {
+            let exponent = (entry.count - limit.max_attempts).min(31);
+            let delay = limit
+                .base_delay
+                .saturating_mul(1 << exponent)
+                .min(limit.max_delay);
+            entry.locked_until = Some(Instant::now() + delay);
+        }
+    }
+}
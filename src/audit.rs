@@ -0,0 +1,137 @@
+// Copyright 2019-2023 Tauri Programme within The Commons Conservancy
+// SPDX-License-Identifier: Apache-2.0
+// SPDX-License-Identifier: MIT
+
+//! Audit log of the operations on secrets, see [`Builder::audit_sink`](crate::Builder::audit_sink).
+//!
+//! Events name the snapshot, client and locations involved, never a secret or a procedure
+//! input. Each event carries a hash chained over every event before it, so a sink that keeps
+//! them all lets [`verify_chain`] detect an event that was changed, removed or reordered.
+
+use std::{
+    path::PathBuf,
+    sync::Mutex,
+    time::{SystemTime, UNIX_EPOCH},
+};
+
+use crypto::hashes::sha::SHA256;
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    paths::{ClientPath, SnapshotPath},
+    LocationDto,
+};
+
+/// The `chain` the first event of a log is chained to.
+pub const GENESIS_CHAIN: &str = "0000000000000000000000000000000000000000000000000000000000000000";
+
+/// Receives the events of the audit log, e.g. to append them to a file or ship them to a
+/// log server.
+///
+/// Events are passed in order, one at a time. A sink must not fail the operation it is told
+/// about, so it handles its own errors.
+pub trait AuditSink: Send + Sync {
+    fn record(&self, event: &AuditEvent);
+}
+
+/// What was done.
+#[derive(Deserialize, Serialize, Clone)]
+#[serde(tag = "type", content = "payload")]
+pub enum AuditOperation {
+    /// A secret was written through `save_secret` or `generate_secret`.
+    WriteSecret { location: LocationDto },
+    /// A secret was read back through `unsafe_get_secret`.
+    ReadSecret { location: LocationDto },
+    /// A secret was deleted through `remove_secret`.
+    DeleteSecret { location: LocationDto },
+    /// A procedure ran, on the secrets at `locations`.
+    Procedure {
+        name: String,
+        locations: Vec<LocationDto>,
+    },
+}
+
+/// An entry of the audit log.
+#[derive(Deserialize, Serialize, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct AuditEvent {
+    /// Position in the log, starting at 0 when the collection is built.
+    pub sequence: u64,
+    /// Milliseconds since the Unix epoch at which the operation finished.
+    pub timestamp_ms: u64,
+    pub snapshot_path: PathBuf,
+    pub client: Vec<u8>,
+    pub operation: AuditOperation,
+    /// Whether the operation succeeded. Failed operations are logged too.
+    pub succeeded: bool,
+    /// Hex encoded SHA-256 of the `chain` of the previous event and this event, see
+    /// [`verify_chain`].
+    pub chain: String,
+}
+
+impl AuditEvent {
+    fn chained_to(&self, previous: &str) -> String {
+        let mut unchained = self.clone();
+        unchained.chain = String::new();
+        let mut data = previous.as_bytes().to_vec();
+        data.extend(serde_json::to_vec(&unchained).unwrap_or_default());
+        let mut hash = [0u8; 32];
+        SHA256(&data, &mut hash);
+        hex::encode(hash)
+    }
+}
+
+/// Checks that `events` follow each other, the first one being chained to `previous`: the
+/// `chain` of the last event seen before, or [`GENESIS_CHAIN`] for a whole log.
+pub fn verify_chain(previous: &str, events: &[AuditEvent]) -> bool {
+    let mut previous = previous.to_string();
+    for event in events {
+        if event.chained_to(&previous) != event.chain {
+            return false;
+        }
+        previous = event.chain.clone();
+    }
+    true
+}
+
+/// Numbers, chains and hands events to the sink.
+pub(crate) struct AuditLog {
+    sink: Box<dyn AuditSink>,
+    /// Sequence and chain of the next event. Locked while the sink runs, to keep the order.
+    next: Mutex<(u64, String)>,
+}
+
+impl AuditLog {
+    pub(crate) fn new(sink: Box<dyn AuditSink>) -> Self {
+        Self {
+            sink,
+            next: Mutex::new((0, GENESIS_CHAIN.to_string())),
+        }
+    }
+
+    pub(crate) fn record(
+        &self,
+        snapshot_path: &SnapshotPath,
+        client: &ClientPath,
+        operation: AuditOperation,
+        succeeded: bool,
+    ) {
+        let timestamp_ms = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .map(|elapsed| elapsed.as_millis() as u64)
+            .unwrap_or_default();
+        let mut next = self.next.lock().unwrap();
+        let mut event = AuditEvent {
+            sequence: next.0,
+            timestamp_ms,
+            snapshot_path: snapshot_path.as_path().to_path_buf(),
+            client: client.as_ref().to_vec(),
+            operation,
+            succeeded,
+            chain: String::new(),
+        };
+        event.chain = event.chained_to(&next.1);
+        self.sink.record(&event);
+        *next = (next.0 + 1, event.chain);
+    }
+}
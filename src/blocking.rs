@@ -0,0 +1,917 @@
+// Copyright 2019-2023 Tauri Programme within The Commons Conservancy
+// SPDX-License-Identifier: Apache-2.0
+// SPDX-License-Identifier: MIT
+
+//! Synchronous equivalents of the crate's `async` API, for callers without an async runtime.
+//!
+//! Every function blocks the current thread until the underlying operation completes.
+
+use std::{
+    future::Future,
+    path::PathBuf,
+    pin::pin,
+    sync::{Arc, Mutex},
+    task::{Context, Poll, Wake, Waker},
+    thread::{self, Thread},
+    time::{Duration, SystemTime},
+};
+
+use iota_stronghold::Client;
+use serde::{de::DeserializeOwned, Serialize};
+use snarkvm_console::network::Network;
+use zeroize::Zeroizing;
+
+#[cfg(feature = "hardware-key")]
+use crate::HardwareKeyFn;
+use crate::{
+    page::{Page, PageRequest},
+    password::Password,
+    paths::{ClientPath, RecordPath, SnapshotPath, VaultPath},
+    progress::ExecutionStage,
+    store_key::StoreKey,
+    stronghold::{HealthReport, Result},
+    BytesDto, PasswordHashFunction, ProcedureDto, StoreRecordMeta, StrongholdCollection,
+};
+
+struct ThreadWaker(Thread);
+
+impl Wake for ThreadWaker {
+    fn wake(self: Arc<Self>) {
+        self.0.unpark();
+    }
+}
+
+/// Drives `future` to completion on the current thread.
+fn block_on<F: Future>(future: F) -> F::Output {
+    let mut future = pin!(future);
+    let waker = Waker::from(Arc::new(ThreadWaker(thread::current())));
+    let mut cx = Context::from_waker(&waker);
+    loop {
+        match future.as_mut().poll(&mut cx) {
+            Poll::Ready(output) => return output,
+            Poll::Pending => thread::park(),
+        }
+    }
+}
+
+pub fn initialize(
+    collection: &StrongholdCollection,
+    hash_function: PasswordHashFunction,
+    snapshot_path: SnapshotPath,
+    password: impl Into<Password>,
+) -> Result<()> {
+    block_on(crate::initialize(
+        collection,
+        hash_function,
+        snapshot_path,
+        password,
+    ))
+}
+
+#[cfg(feature = "hardware-key")]
+pub fn initialize_with_hardware_key(
+    collection: &StrongholdCollection,
+    key_source: &HardwareKeyFn,
+    snapshot_path: SnapshotPath,
+) -> Result<()> {
+    block_on(crate::initialize_with_hardware_key(
+        collection,
+        key_source,
+        snapshot_path,
+    ))
+}
+
+pub fn initialize_combined(
+    collection: &StrongholdCollection,
+    hash_functions: Vec<PasswordHashFunction>,
+    snapshot_path: SnapshotPath,
+    passwords: Vec<impl Into<Password>>,
+) -> Result<()> {
+    block_on(crate::initialize_combined(
+        collection,
+        hash_functions,
+        snapshot_path,
+        passwords,
+    ))
+}
+
+/// Like [`initialize`] for several snapshots at once, hashing their passwords and opening them
+/// on one thread each, so that the key derivations run in parallel. Returns the outcome for
+/// every snapshot, in the order of `snapshots`.
+///
+/// Every password is wiped once hashed, whether or not its snapshot opens. Only available
+/// here, since waiting for the threads would block the executor of an async caller.
+pub fn initialize_many<P: Into<Password> + Send>(
+    collection: &StrongholdCollection,
+    hash_function: PasswordHashFunction,
+    snapshots: Vec<(SnapshotPath, P)>,
+) -> Vec<(SnapshotPath, Result<()>)> {
+    let opened: Vec<_> = thread::scope(|scope| {
+        let threads: Vec<_> = snapshots
+            .into_iter()
+            .map(|(snapshot_path, password)| {
+                let hash_function = &hash_function;
+                let thread = scope.spawn(move || {
+                    let password: Password = password.into();
+                    collection.attempts.check(&snapshot_path)?;
+                    let hash = (hash_function.0)(&password);
+                    drop(password);
+                    crate::open_stronghold(collection, &snapshot_path, hash)
+                });
+                (snapshot_path, thread)
+            })
+            .collect();
+        threads
+            .into_iter()
+            .map(|(snapshot_path, thread)| {
+                let stronghold = thread
+                    .join()
+                    .unwrap_or_else(|panic| std::panic::resume_unwind(panic));
+                (snapshot_path, stronghold)
+            })
+            .collect()
+    });
+
+    opened
+        .into_iter()
+        .map(|(snapshot_path, stronghold)| {
+            let result = stronghold.and_then(|stronghold| {
+                collection
+                    .snapshots
+                    .lock()
+                    .unwrap()
+                    .insert(snapshot_path.clone(), Arc::new(Mutex::new(stronghold)));
+                block_on(crate::run_unlock_hook(collection, snapshot_path.clone()))
+            });
+            (snapshot_path, result)
+        })
+        .collect()
+}
+
+pub fn is_initialized(collection: &StrongholdCollection, snapshot_path: SnapshotPath) -> bool {
+    block_on(crate::is_initialized(collection, snapshot_path))
+}
+
+pub fn collection_paths(collection: &StrongholdCollection) -> Vec<PathBuf> {
+    block_on(crate::collection_paths(collection))
+}
+
+pub fn try_initialize(
+    collection: &StrongholdCollection,
+    hash_function: PasswordHashFunction,
+    snapshot_path: SnapshotPath,
+    password: impl Into<Password>,
+) -> Result<()> {
+    block_on(crate::try_initialize(
+        collection,
+        hash_function,
+        snapshot_path,
+        password,
+    ))
+}
+
+pub fn initialize_with_confirmation(
+    collection: &StrongholdCollection,
+    hash_function: PasswordHashFunction,
+    snapshot_path: SnapshotPath,
+    password: impl Into<Password>,
+    confirmation: impl Into<Password>,
+) -> Result<()> {
+    block_on(crate::initialize_with_confirmation(
+        collection,
+        hash_function,
+        snapshot_path,
+        password,
+        confirmation,
+    ))
+}
+
+pub fn verify_password(
+    collection: &StrongholdCollection,
+    hash_function: PasswordHashFunction,
+    snapshot_path: SnapshotPath,
+    password: impl Into<Password>,
+) -> Result<bool> {
+    block_on(crate::verify_password(
+        collection,
+        hash_function,
+        snapshot_path,
+        password,
+    ))
+}
+
+pub fn reinitialize_hash_function(
+    collection: &StrongholdCollection,
+    snapshot_path: SnapshotPath,
+    hash_function: PasswordHashFunction,
+) -> Result<()> {
+    block_on(crate::reinitialize_hash_function(
+        collection,
+        snapshot_path,
+        hash_function,
+    ))
+}
+
+pub fn reencrypt_with_new_kdf(
+    collection: &StrongholdCollection,
+    hash_function: PasswordHashFunction,
+    snapshot_path: SnapshotPath,
+    password: impl Into<Password>,
+) -> Result<()> {
+    block_on(crate::reencrypt_with_new_kdf(
+        collection,
+        hash_function,
+        snapshot_path,
+        password,
+    ))
+}
+
+pub fn destroy(collection: &StrongholdCollection, snapshot_path: SnapshotPath) -> Result<()> {
+    block_on(crate::destroy(collection, snapshot_path))
+}
+
+pub fn shutdown(collection: &StrongholdCollection) -> Result<()> {
+    block_on(crate::shutdown(collection))
+}
+
+pub fn save(collection: &StrongholdCollection, snapshot_path: SnapshotPath) -> Result<()> {
+    block_on(crate::save(collection, snapshot_path))
+}
+
+pub fn force_save(collection: &StrongholdCollection, snapshot_path: SnapshotPath) -> Result<()> {
+    block_on(crate::force_save(collection, snapshot_path))
+}
+
+pub fn snapshot_last_modified(
+    collection: &StrongholdCollection,
+    snapshot_path: SnapshotPath,
+) -> Result<SystemTime> {
+    block_on(crate::snapshot_last_modified(collection, snapshot_path))
+}
+
+pub fn snapshot_dirty_since(
+    collection: &StrongholdCollection,
+    snapshot_path: SnapshotPath,
+) -> Result<Option<SystemTime>> {
+    block_on(crate::snapshot_dirty_since(collection, snapshot_path))
+}
+
+pub fn health_check(
+    collection: &StrongholdCollection,
+    snapshot_path: SnapshotPath,
+) -> Result<HealthReport> {
+    block_on(crate::health_check(collection, snapshot_path))
+}
+
+pub fn verify_snapshot_integrity(
+    collection: &StrongholdCollection,
+    snapshot_path: SnapshotPath,
+) -> Result<bool> {
+    block_on(crate::verify_snapshot_integrity(collection, snapshot_path))
+}
+
+pub fn create_client(
+    collection: &StrongholdCollection,
+    snapshot_path: SnapshotPath,
+    client: ClientPath,
+) -> Result<()> {
+    block_on(crate::create_client(collection, snapshot_path, client))
+}
+
+pub fn load_client(
+    collection: &StrongholdCollection,
+    snapshot_path: SnapshotPath,
+    client: ClientPath,
+) -> Result<()> {
+    block_on(crate::load_client(collection, snapshot_path, client))
+}
+
+pub fn try_get_client(
+    collection: &StrongholdCollection,
+    snapshot_path: SnapshotPath,
+    client: ClientPath,
+) -> Result<Option<Client>> {
+    block_on(crate::try_get_client(collection, snapshot_path, client))
+}
+
+pub fn mark_snapshot_dirty(
+    collection: &StrongholdCollection,
+    snapshot_path: SnapshotPath,
+) -> Result<()> {
+    block_on(crate::mark_snapshot_dirty(collection, snapshot_path))
+}
+
+pub fn load_or_create_client(
+    collection: &StrongholdCollection,
+    snapshot_path: SnapshotPath,
+    client: ClientPath,
+) -> Result<bool> {
+    block_on(crate::load_or_create_client(
+        collection,
+        snapshot_path,
+        client,
+    ))
+}
+
+pub fn reload_client(
+    collection: &StrongholdCollection,
+    snapshot_path: SnapshotPath,
+    client: ClientPath,
+) -> Result<()> {
+    block_on(crate::reload_client(collection, snapshot_path, client))
+}
+
+pub fn migrate_between_snapshots(
+    collection: &StrongholdCollection,
+    from: SnapshotPath,
+    to: SnapshotPath,
+    clients: Vec<ClientPath>,
+) -> Result<()> {
+    block_on(crate::migrate_between_snapshots(
+        collection, from, to, clients,
+    ))
+}
+
+pub fn next_counter(
+    collection: &StrongholdCollection,
+    snapshot_path: SnapshotPath,
+    client: ClientPath,
+    vault: BytesDto,
+) -> Result<usize> {
+    block_on(crate::next_counter(
+        collection,
+        snapshot_path,
+        client,
+        vault,
+    ))
+}
+
+pub fn get_store_record(
+    collection: &StrongholdCollection,
+    snapshot_path: SnapshotPath,
+    client: ClientPath,
+    key: String,
+) -> Result<Option<Vec<u8>>> {
+    block_on(crate::get_store_record(
+        collection,
+        snapshot_path,
+        client,
+        key,
+    ))
+}
+
+pub fn get_store_record_namespaced(
+    collection: &StrongholdCollection,
+    snapshot_path: SnapshotPath,
+    client: ClientPath,
+    key: StoreKey,
+) -> Result<Option<Vec<u8>>> {
+    block_on(crate::get_store_record_namespaced(
+        collection,
+        snapshot_path,
+        client,
+        key,
+    ))
+}
+
+pub fn get_store_record_or(
+    collection: &StrongholdCollection,
+    snapshot_path: SnapshotPath,
+    client: ClientPath,
+    key: String,
+    default: Vec<u8>,
+) -> Result<Vec<u8>> {
+    block_on(crate::get_store_record_or(
+        collection,
+        snapshot_path,
+        client,
+        key,
+        default,
+    ))
+}
+
+pub fn save_store_record(
+    collection: &StrongholdCollection,
+    snapshot_path: SnapshotPath,
+    client: ClientPath,
+    key: String,
+    value: Vec<u8>,
+    lifetime: Option<Duration>,
+) -> Result<Option<Vec<u8>>> {
+    block_on(crate::save_store_record(
+        collection,
+        snapshot_path,
+        client,
+        key,
+        value,
+        lifetime,
+    ))
+}
+
+pub fn save_store_record_namespaced(
+    collection: &StrongholdCollection,
+    snapshot_path: SnapshotPath,
+    client: ClientPath,
+    key: StoreKey,
+    value: Vec<u8>,
+    lifetime: Option<Duration>,
+) -> Result<Option<Vec<u8>>> {
+    block_on(crate::save_store_record_namespaced(
+        collection,
+        snapshot_path,
+        client,
+        key,
+        value,
+        lifetime,
+    ))
+}
+
+pub fn peek_store_record(
+    collection: &StrongholdCollection,
+    snapshot_path: SnapshotPath,
+    client: ClientPath,
+    key: String,
+) -> Result<Option<StoreRecordMeta>> {
+    block_on(crate::peek_store_record(
+        collection,
+        snapshot_path,
+        client,
+        key,
+    ))
+}
+
+pub fn peek_store_record_namespaced(
+    collection: &StrongholdCollection,
+    snapshot_path: SnapshotPath,
+    client: ClientPath,
+    key: StoreKey,
+) -> Result<Option<StoreRecordMeta>> {
+    block_on(crate::peek_store_record_namespaced(
+        collection,
+        snapshot_path,
+        client,
+        key,
+    ))
+}
+
+pub fn list_store_records(
+    collection: &StrongholdCollection,
+    snapshot_path: SnapshotPath,
+    client: ClientPath,
+    page: PageRequest,
+) -> Result<Page<(String, StoreRecordMeta)>> {
+    block_on(crate::list_store_records(
+        collection,
+        snapshot_path,
+        client,
+        page,
+    ))
+}
+
+pub fn list_store_records_namespaced(
+    collection: &StrongholdCollection,
+    snapshot_path: SnapshotPath,
+    client: ClientPath,
+    namespace: String,
+    page: PageRequest,
+) -> Result<Page<(String, StoreRecordMeta)>> {
+    block_on(crate::list_store_records_namespaced(
+        collection,
+        snapshot_path,
+        client,
+        namespace,
+        page,
+    ))
+}
+
+pub fn set_store_record_lifetime(
+    collection: &StrongholdCollection,
+    snapshot_path: SnapshotPath,
+    client: ClientPath,
+    key: String,
+    lifetime: Option<Duration>,
+) -> Result<()> {
+    block_on(crate::set_store_record_lifetime(
+        collection,
+        snapshot_path,
+        client,
+        key,
+        lifetime,
+    ))
+}
+
+pub fn save_typed<T: Serialize>(
+    collection: &StrongholdCollection,
+    snapshot_path: SnapshotPath,
+    client: ClientPath,
+    key: String,
+    value: &T,
+    lifetime: Option<Duration>,
+) -> Result<()> {
+    block_on(crate::save_typed(
+        collection,
+        snapshot_path,
+        client,
+        key,
+        value,
+        lifetime,
+    ))
+}
+
+pub fn get_typed<T: DeserializeOwned>(
+    collection: &StrongholdCollection,
+    snapshot_path: SnapshotPath,
+    client: ClientPath,
+    key: String,
+) -> Result<Option<T>> {
+    block_on(crate::get_typed(collection, snapshot_path, client, key))
+}
+
+#[allow(clippy::too_many_arguments)]
+pub fn save_store_record_sealed(
+    collection: &StrongholdCollection,
+    snapshot_path: SnapshotPath,
+    client: ClientPath,
+    key: String,
+    value: Vec<u8>,
+    lifetime: Option<Duration>,
+    sealing_vault: VaultPath,
+    sealing_record: RecordPath,
+) -> Result<()> {
+    block_on(crate::save_store_record_sealed(
+        collection,
+        snapshot_path,
+        client,
+        key,
+        value,
+        lifetime,
+        sealing_vault,
+        sealing_record,
+    ))
+}
+
+pub fn get_store_record_sealed(
+    collection: &StrongholdCollection,
+    snapshot_path: SnapshotPath,
+    client: ClientPath,
+    key: String,
+    sealing_vault: VaultPath,
+    sealing_record: RecordPath,
+) -> Result<Option<Vec<u8>>> {
+    block_on(crate::get_store_record_sealed(
+        collection,
+        snapshot_path,
+        client,
+        key,
+        sealing_vault,
+        sealing_record,
+    ))
+}
+
+pub fn remove_store_record(
+    collection: &StrongholdCollection,
+    snapshot_path: SnapshotPath,
+    client: ClientPath,
+    key: String,
+) -> Result<Option<Vec<u8>>> {
+    block_on(crate::remove_store_record(
+        collection,
+        snapshot_path,
+        client,
+        key,
+    ))
+}
+
+pub fn remove_store_record_namespaced(
+    collection: &StrongholdCollection,
+    snapshot_path: SnapshotPath,
+    client: ClientPath,
+    key: StoreKey,
+) -> Result<Option<Vec<u8>>> {
+    block_on(crate::remove_store_record_namespaced(
+        collection,
+        snapshot_path,
+        client,
+        key,
+    ))
+}
+
+pub fn compare_and_swap_store_record(
+    collection: &StrongholdCollection,
+    snapshot_path: SnapshotPath,
+    client: ClientPath,
+    key: String,
+    expected: Option<Vec<u8>>,
+    new: Vec<u8>,
+) -> Result<()> {
+    block_on(crate::compare_and_swap_store_record(
+        collection,
+        snapshot_path,
+        client,
+        key,
+        expected,
+        new,
+    ))
+}
+
+pub fn get_multiple_store_records(
+    collection: &StrongholdCollection,
+    snapshot_path: SnapshotPath,
+    client: ClientPath,
+    keys: Vec<String>,
+) -> Result<Vec<Option<Vec<u8>>>> {
+    block_on(crate::get_multiple_store_records(
+        collection,
+        snapshot_path,
+        client,
+        keys,
+    ))
+}
+
+pub fn save_session_record(
+    collection: &StrongholdCollection,
+    snapshot_path: SnapshotPath,
+    client: ClientPath,
+    key: String,
+    value: Vec<u8>,
+) -> Result<Option<Vec<u8>>> {
+    block_on(crate::save_session_record(
+        collection,
+        snapshot_path,
+        client,
+        key,
+        value,
+    ))
+}
+
+pub fn get_session_record(
+    collection: &StrongholdCollection,
+    snapshot_path: SnapshotPath,
+    client: ClientPath,
+    key: String,
+) -> Result<Option<Vec<u8>>> {
+    block_on(crate::get_session_record(
+        collection,
+        snapshot_path,
+        client,
+        key,
+    ))
+}
+
+pub fn remove_session_record(
+    collection: &StrongholdCollection,
+    snapshot_path: SnapshotPath,
+    client: ClientPath,
+    key: String,
+) -> Result<Option<Vec<u8>>> {
+    block_on(crate::remove_session_record(
+        collection,
+        snapshot_path,
+        client,
+        key,
+    ))
+}
+
+pub fn get_store_records_by_prefix(
+    collection: &StrongholdCollection,
+    snapshot_path: SnapshotPath,
+    client: ClientPath,
+    prefix: String,
+) -> Result<Vec<(String, Vec<u8>)>> {
+    block_on(crate::get_store_records_by_prefix(
+        collection,
+        snapshot_path,
+        client,
+        prefix,
+    ))
+}
+
+pub fn get_store_records_by_prefix_namespaced(
+    collection: &StrongholdCollection,
+    snapshot_path: SnapshotPath,
+    client: ClientPath,
+    namespace: String,
+    prefix: String,
+) -> Result<Vec<(String, Vec<u8>)>> {
+    block_on(crate::get_store_records_by_prefix_namespaced(
+        collection,
+        snapshot_path,
+        client,
+        namespace,
+        prefix,
+    ))
+}
+
+pub fn list_store_keys_in_namespace(
+    collection: &StrongholdCollection,
+    snapshot_path: SnapshotPath,
+    client: ClientPath,
+    namespace: String,
+    page: PageRequest,
+) -> Result<Page<StoreKey>> {
+    block_on(crate::list_store_keys_in_namespace(
+        collection,
+        snapshot_path,
+        client,
+        page,
+    ))
+}
+
+pub fn save_secret(
+    collection: &StrongholdCollection,
+    snapshot_path: SnapshotPath,
+    client: ClientPath,
+    vault: VaultPath,
+    record_path: RecordPath,
+    secret: Vec<u8>,
+    overwrite: bool,
+) -> Result<()> {
+    block_on(crate::save_secret(
+        collection,
+        snapshot_path,
+        client,
+        vault,
+        record_path,
+        secret,
+        overwrite,
+    ))
+}
+
+pub fn generate_secret(
+    collection: &StrongholdCollection,
+    snapshot_path: SnapshotPath,
+    client: ClientPath,
+    vault: VaultPath,
+    record_path: RecordPath,
+    size_bytes: usize,
+) -> Result<()> {
+    block_on(crate::generate_secret(
+        collection,
+        snapshot_path,
+        client,
+        vault,
+        record_path,
+        size_bytes,
+    ))
+}
+
+pub fn secret_exists(
+    collection: &StrongholdCollection,
+    snapshot_path: SnapshotPath,
+    client: ClientPath,
+    vault: VaultPath,
+    record_path: RecordPath,
+) -> Result<bool> {
+    block_on(crate::secret_exists(
+        collection,
+        snapshot_path,
+        client,
+        vault,
+        record_path,
+    ))
+}
+
+pub fn unsafe_get_secret(
+    collection: &StrongholdCollection,
+    snapshot_path: SnapshotPath,
+    client: ClientPath,
+    vault: VaultPath,
+    record_path: RecordPath,
+) -> Result<Zeroizing<Vec<u8>>> {
+    block_on(crate::unsafe_get_secret(
+        collection,
+        snapshot_path,
+        client,
+        vault,
+        record_path,
+    ))
+}
+
+pub fn remove_secret(
+    collection: &StrongholdCollection,
+    snapshot_path: SnapshotPath,
+    client: ClientPath,
+    vault: VaultPath,
+    record_path: RecordPath,
+) -> Result<()> {
+    block_on(crate::remove_secret(
+        collection,
+        snapshot_path,
+        client,
+        vault,
+        record_path,
+    ))
+}
+
+pub fn list_vault_records(
+    collection: &StrongholdCollection,
+    snapshot_path: SnapshotPath,
+    client: ClientPath,
+    vault: VaultPath,
+    page: PageRequest,
+) -> Result<Page<Vec<u8>>> {
+    block_on(crate::list_vault_records(
+        collection,
+        snapshot_path,
+        client,
+        vault,
+        page,
+    ))
+}
+
+pub fn list_clients(
+    collection: &StrongholdCollection,
+    snapshot_path: SnapshotPath,
+    page: PageRequest,
+) -> Result<Page<Vec<u8>>> {
+    block_on(crate::list_clients(collection, snapshot_path, page))
+}
+
+pub fn list_vaults(
+    collection: &StrongholdCollection,
+    snapshot_path: SnapshotPath,
+    client: ClientPath,
+    page: PageRequest,
+) -> Result<Page<Vec<u8>>> {
+    block_on(crate::list_vaults(collection, snapshot_path, client, page))
+}
+
+/// Runs `procedure` on the calling thread.
+///
+/// Proving procedures such as `AleoExecute` can block for a long time, so avoid calling
+/// this from a thread that must stay responsive.
+pub fn execute_procedure<N: Network>(
+    collection: &StrongholdCollection,
+    snapshot_path: SnapshotPath,
+    client: ClientPath,
+    procedure: ProcedureDto<N>,
+) -> Result<Vec<u8>> {
+    block_on(crate::execute_procedure(
+        collection,
+        snapshot_path,
+        client,
+        procedure,
+    ))
+}
+
+pub fn execute_procedure_with_timeout<N: Network>(
+    collection: &StrongholdCollection,
+    snapshot_path: SnapshotPath,
+    client: ClientPath,
+    procedure: ProcedureDto<N>,
+    timeout: Option<Duration>,
+) -> Result<Vec<u8>> {
+    block_on(crate::execute_procedure_with_timeout(
+        collection,
+        snapshot_path,
+        client,
+        procedure,
+        timeout,
+    ))
+}
+
+pub fn execute_procedure_with_progress<N: Network>(
+    collection: &StrongholdCollection,
+    snapshot_path: SnapshotPath,
+    client: ClientPath,
+    procedure: ProcedureDto<N>,
+    on_progress: impl Fn(ExecutionStage),
+) -> Result<Vec<u8>> {
+    block_on(crate::execute_procedure_with_progress(
+        collection,
+        snapshot_path,
+        client,
+        procedure,
+        on_progress,
+    ))
+}
+
+pub fn execute_procedure_with_ephemeral_secrets<N: Network>(
+    collection: &StrongholdCollection,
+    snapshot_path: SnapshotPath,
+    client: ClientPath,
+    secrets: Vec<(RecordPath, Zeroizing<Vec<u8>>)>,
+    procedure: ProcedureDto<N>,
+) -> Result<Vec<u8>> {
+    block_on(crate::execute_procedure_with_ephemeral_secrets(
+        collection,
+        snapshot_path,
+        client,
+        secrets,
+        procedure,
+    ))
+}
+
+pub fn execute_procedures<N: Network>(
+    collection: &StrongholdCollection,
+    snapshot_path: SnapshotPath,
+    client: ClientPath,
+    procedures: Vec<ProcedureDto<N>>,
+) -> Result<Vec<Vec<u8>>> {
+    block_on(crate::execute_procedures(
+        collection,
+        snapshot_path,
+        client,
+        procedures,
+    ))
+}
@@ -0,0 +1,48 @@
+// Copyright 2019-2023 Tauri Programme within The Commons Conservancy
+// SPDX-License-Identifier: Apache-2.0
+// SPDX-License-Identifier: MIT
+
+//! Encodings of the values written by [`save_typed`](crate::save_typed) and read by
+//! [`get_typed`](crate::get_typed).
+
+use serde::{de::DeserializeOwned, Serialize};
+
+use crate::stronghold::{Error, Result};
+
+/// How typed store values are encoded. Set for a collection with
+/// [`Builder::store_codec`](crate::Builder::store_codec).
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum Codec {
+    #[default]
+    Json,
+    #[cfg(feature = "cbor")]
+    Cbor,
+}
+
+impl Codec {
+    pub fn encode<T: Serialize>(self, value: &T) -> Result<Vec<u8>> {
+        match self {
+            Codec::Json => serde_json::to_vec(value).map_err(Into::into),
+            #[cfg(feature = "cbor")]
+            Codec::Cbor => {
+                let mut bytes = Vec::new();
+                ciborium::into_writer(value, &mut bytes).map_err(codec_error)?;
+                Ok(bytes)
+            }
+        }
+    }
+
+    pub fn decode<T: DeserializeOwned>(self, bytes: &[u8]) -> Result<T> {
+        match self {
+            Codec::Json => serde_json::from_slice(bytes).map_err(Into::into),
+            #[cfg(feature = "cbor")]
+            Codec::Cbor => ciborium::from_reader(bytes).map_err(codec_error),
+        }
+    }
+}
+
+/// The message of the encoder may quote the value, which is kept out of the display.
+#[cfg(feature = "cbor")]
+fn codec_error(e: impl std::fmt::Display) -> Error {
+    Error::Codec(crate::stronghold::Redacted::new(e.to_string()))
+}
@@ -0,0 +1,40 @@
+// Copyright 2019-2023 Tauri Programme within The Commons Conservancy
+// SPDX-License-Identifier: Apache-2.0
+// SPDX-License-Identifier: MIT
+
+//! Folds several password hashes into one snapshot key, for split-knowledge unlocking such as
+//! a user PIN combined with a device secret.
+//!
+//! The combiner is HKDF-SHA256 (RFC 5869) with [`COMBINED_KEY_SALT`] as salt and
+//! [`COMBINED_KEY_INFO`] as info. The input key material is the concatenation of the hashes in
+//! the order they were given, each prefixed with its length as a big-endian `u32`. Every hash
+//! is needed to rebuild the key: this is N-of-N, not a threshold scheme.
+
+use crypto::macs::hmac::HMAC_SHA256;
+use zeroize::Zeroizing;
+
+/// HKDF salt of the combiner.
+pub const COMBINED_KEY_SALT: &[u8] = b"tauri-plugin-stronghold combined key v1";
+
+/// HKDF info of the combiner.
+pub const COMBINED_KEY_INFO: &[u8] = b"stronghold snapshot key";
+
+/// Derives the 32 byte snapshot key from `hashes`.
+pub fn combine_hashes(hashes: &[Zeroizing<Vec<u8>>]) -> Zeroizing<Vec<u8>> {
+    let mut ikm = Zeroizing::new(Vec::new());
+    for hash in hashes {
+        ikm.extend_from_slice(&(hash.len() as u32).to_be_bytes());
+        ikm.extend_from_slice(hash);
+    }
+
+    // Extract.
+    let mut prk = Zeroizing::new([0u8; 32]);
+    HMAC_SHA256(&ikm, COMBINED_KEY_SALT, &mut prk);
+
+    // Expand, a single block is enough for 32 bytes.
+    let mut block = Zeroizing::new(COMBINED_KEY_INFO.to_vec());
+    block.push(1);
+    let mut key = Zeroizing::new([0u8; 32]);
+    HMAC_SHA256(&block, &*prk, &mut key);
+    Zeroizing::new(key.to_vec())
+}
@@ -0,0 +1,107 @@
+// Copyright 2019-2023 Tauri Programme within The Commons Conservancy
+// SPDX-License-Identifier: Apache-2.0
+// SPDX-License-Identifier: MIT
+
+//! Coalesces the saves of snapshots written in bursts.
+
+use std::{
+    collections::HashMap,
+    sync::{Arc, Mutex, Weak},
+    thread,
+    time::{Duration, Instant},
+};
+
+use crate::{paths::SnapshotPath, stronghold::Stronghold};
+
+/// Lower bound for how often pending saves are checked.
+const MIN_TICK: Duration = Duration::from_millis(10);
+
+type Snapshots = Mutex<HashMap<SnapshotPath, Arc<Mutex<Stronghold>>>>;
+
+/// When written snapshots are saved automatically.
+#[derive(Clone, Copy, Debug)]
+pub struct SaveDebounce {
+    /// Quiet time after the last write before the snapshot is saved.
+    pub window: Duration,
+    /// Longest a write waits to be saved while writes keep coming.
+    pub max_delay: Duration,
+}
+
+struct Dirty {
+    first_write: Instant,
+    last_write: Instant,
+}
+
+/// Tracks the snapshots with unsaved writes and saves them from a background thread.
+pub(crate) struct Debouncer {
+    dirty: Arc<Mutex<HashMap<SnapshotPath, Dirty>>>,
+}
+
+impl Debouncer {
+    /// Starts the saving thread. It stops once `snapshots` is dropped with its collection.
+    pub(crate) fn start(config: SaveDebounce, snapshots: Weak<Snapshots>) -> Self {
+        let dirty: Arc<Mutex<HashMap<SnapshotPath, Dirty>>> = Default::default();
+        let tick = (config.window.min(config.max_delay) / 4).max(MIN_TICK);
+        let pending = dirty.clone();
+        thread::spawn(move || loop {
+            thread::sleep(tick);
+            let Some(snapshots) = snapshots.upgrade() else {
+                break;
+            };
+            let due: Vec<SnapshotPath> = {
+                let mut pending = pending.lock().unwrap();
+                let due = pending
+                    .iter()
+                    .filter(|(_, dirty)| {
+                        dirty.last_write.elapsed() >= config.window
+                            || dirty.first_write.elapsed() >= config.max_delay
+                    })
+                    .map(|(path, _)| path.clone())
+                    .collect::<Vec<_>>();
+                for path in &due {
+                    pending.remove(path);
+                }
+                due
+            };
+            for path in due {
+                let stronghold = snapshots.lock().unwrap().get(&path).cloned();
+                let Some(stronghold) = stronghold else {
+                    continue;
+                };
+                let result = stronghold.lock().unwrap().save();
+                if let Err(e) = result {
+                    log::error!("automatic save failed: {e}");
+                    // Retried after another window.
+                    mark(&pending, path);
+                }
+            }
+        });
+        Self { dirty }
+    }
+
+    pub(crate) fn mark_dirty(&self, path: &SnapshotPath) {
+        mark(&self.dirty, path.clone());
+    }
+
+    /// Forgets the pending save of `path`, after it was saved explicitly.
+    pub(crate) fn mark_clean(&self, path: &SnapshotPath) {
+        self.dirty.lock().unwrap().remove(path);
+    }
+
+    pub(crate) fn clear(&self) {
+        self.dirty.lock().unwrap().clear();
+    }
+}
+
+fn mark(dirty: &Mutex<HashMap<SnapshotPath, Dirty>>, path: SnapshotPath) {
+    let now = Instant::now();
+    dirty
+        .lock()
+        .unwrap()
+        .entry(path)
+        .and_modify(|dirty| dirty.last_write = now)
+        .or_insert(Dirty {
+            first_write: now,
+            last_write: now,
+        });
+}
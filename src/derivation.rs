@@ -0,0 +1,64 @@
+// Copyright 2019-2023 Tauri Programme within The Commons Conservancy
+// SPDX-License-Identifier: Apache-2.0
+// SPDX-License-Identifier: MIT
+
+use crate::stronghold::{Error, Result};
+
+/// Bit set on a chain index to mark it as hardened.
+pub const HARDENED: u32 = 1 << 31;
+
+/// Parses a BIP44-style derivation path such as `m/44'/0'/0'/0/0` into a SLIP10 chain.
+///
+/// Hardened segments may be marked with `'`, `h` or `H`. Every index must be below 2^31,
+/// since the top bit is reserved for hardening.
+pub fn derivation_path_from_string(path: &str) -> Result<Vec<u32>> {
+    let mut segments = path.trim().split('/');
+    if segments.next() != Some("m") {
+        return Err(invalid(path, "must start with `m`"));
+    }
+
+    segments
+        .map(|segment| {
+            let (index, hardened) = match segment
+                .strip_suffix('\'')
+                .or_else(|| segment.strip_suffix('h'))
+                .or_else(|| segment.strip_suffix('H'))
+            {
+                Some(index) => (index, true),
+                None => (segment, false),
+            };
+            if index.is_empty() || !index.bytes().all(|b| b.is_ascii_digit()) {
+                return Err(invalid(path, format!("`{segment}` is not a valid index")));
+            }
+            let index: u32 = index
+                .parse()
+                .ok()
+                .filter(|index| index & HARDENED == 0)
+                .ok_or_else(|| invalid(path, format!("`{segment}` is out of range")))?;
+            Ok(if hardened { index | HARDENED } else { index })
+        })
+        .collect()
+}
+
+/// SLIP-0044 coin type of Aleo.
+pub const ALEO_COIN_TYPE: u32 = 683;
+
+/// The chain of the Aleo account `index`, `m/44'/683'/{index}'/0'`.
+pub fn aleo_account_chain(index: u32) -> Result<Vec<u32>> {
+    if index & HARDENED != 0 {
+        return Err(Error::invalid_input(
+            "index",
+            format!("must be below {HARDENED}"),
+        ));
+    }
+    Ok(vec![
+        44 | HARDENED,
+        ALEO_COIN_TYPE | HARDENED,
+        index | HARDENED,
+        HARDENED,
+    ])
+}
+
+fn invalid(path: &str, detail: impl std::fmt::Display) -> Error {
+    Error::invalid_input("derivation path", format!("`{path}` {detail}"))
+}
@@ -0,0 +1,59 @@
+// Copyright 2019-2023 Tauri Programme within The Commons Conservancy
+// SPDX-License-Identifier: Apache-2.0
+// SPDX-License-Identifier: MIT
+
+//! Display forms of addresses and public keys, so that every frontend shows the same string
+//! for the same account. Pure formatting: nothing here touches a snapshot.
+
+use std::str::FromStr;
+
+use snarkvm_console::{account::Address, network::Network};
+
+use crate::{
+    output::{AddressDisplay, KeyEncoding},
+    stronghold::{Error, Result},
+};
+
+/// Characters kept at each end of the short form by default.
+pub const DEFAULT_SHORT_CHARS: usize = 6;
+
+/// Human-readable part of Aleo addresses, kept whole in the short form.
+const ADDRESS_PREFIX: &str = "aleo1";
+
+/// Displays the Aleo address `address`, checked against the network `N`.
+///
+/// The canonical form is the lowercase bech32 string, whatever the case `address` was given
+/// in. The short form keeps the `aleo1` prefix and `chars` characters at each end, e.g.
+/// `aleo1abcdef…uvwxyz` with the default [`DEFAULT_SHORT_CHARS`].
+pub fn display_address<N: Network>(address: &str, chars: usize) -> Result<AddressDisplay> {
+    let address = Address::<N>::from_str(&address.to_lowercase())
+        .map_err(|e| Error::invalid_value("address", e.to_string()))?;
+    let canonical = address.to_string();
+    let short = shorten(&canonical, ADDRESS_PREFIX.len(), chars);
+    Ok(AddressDisplay { canonical, short })
+}
+
+/// Displays the raw public key `public_key`, e.g. as returned by the `PublicKey` procedure,
+/// in `encoding`, with `chars` characters at each end of the short form.
+pub fn display_public_key(
+    public_key: &[u8],
+    encoding: KeyEncoding,
+    chars: usize,
+) -> AddressDisplay {
+    let canonical = encoding.encode(public_key);
+    let short = shorten(&canonical, 0, chars);
+    AddressDisplay { canonical, short }
+}
+
+/// Keeps the first `prefix` characters of `canonical`, then `chars` at each end.
+///
+/// Strings that the ellipsis wouldn't make shorter are returned whole.
+fn shorten(canonical: &str, prefix: usize, chars: usize) -> String {
+    let len = canonical.chars().count();
+    if len <= prefix + 2 * chars + 1 {
+        return canonical.to_string();
+    }
+    let head: String = canonical.chars().take(prefix + chars).collect();
+    let tail: String = canonical.chars().skip(len - chars).collect();
+    format!("{head}…{tail}")
+}
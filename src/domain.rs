@@ -0,0 +1,44 @@
+// Copyright 2019-2023 Tauri Programme within The Commons Conservancy
+// SPDX-License-Identifier: Apache-2.0
+// SPDX-License-Identifier: MIT
+
+//! Checks of the `ext` domain separator taken by the Aleo signing procedures.
+//!
+//! A signature only proves that the key signed `msg` under `ext`. Give every kind of message
+//! its own `ext` naming the application and the purpose, e.g. `my_wallet_login`, and never
+//! sign two kinds of message under the same one: a signature made for one context could
+//! otherwise be replayed in the other.
+
+use snarkvm_console::{network::Network, program::Identifier};
+
+use crate::stronghold::{Error, Result};
+
+/// Names too generic to separate anything, or used by the Aleo protocol itself.
+pub const RESERVED_EXTS: &[&str] = &[
+    "credits",
+    "default",
+    "fee",
+    "main",
+    "message",
+    "sign",
+    "signature",
+    "test",
+    "transfer",
+];
+
+/// Prefix reserved for the protocol's own domain separators.
+pub const RESERVED_EXT_PREFIX: &str = "aleo";
+
+/// Rejects an `ext` that is reserved or starts with [`RESERVED_EXT_PREFIX`], ignoring case.
+///
+/// Malformed identifiers, including the empty one, are already rejected when deserializing.
+pub fn validate_ext<N: Network>(ext: &Identifier<N>) -> Result<()> {
+    let name = ext.to_string().to_lowercase();
+    if RESERVED_EXTS.contains(&name.as_str()) || name.starts_with(RESERVED_EXT_PREFIX) {
+        return Err(Error::invalid_input(
+            "ext",
+            format!("`{ext}` is reserved, use a name specific to the application and purpose"),
+        ));
+    }
+    Ok(())
+}
@@ -0,0 +1,82 @@
+// Copyright 2019-2023 Tauri Programme within The Commons Conservancy
+// SPDX-License-Identifier: Apache-2.0
+// SPDX-License-Identifier: MIT
+
+//! Opt-in memoization of `AleoExecute` outputs, see
+//! [`Builder::execution_cache`](crate::Builder::execution_cache).
+
+use std::{collections::VecDeque, sync::Mutex};
+
+use crypto::hashes::sha::SHA256;
+
+use crate::{
+    paths::{ClientPath, SnapshotPath},
+    stronghold::Result,
+};
+
+/// The most recent outputs of identical requests, kept in memory only.
+///
+/// Requests are identified by a hash, so their inputs aren't kept around.
+#[derive(Default)]
+pub(crate) struct ExecutionCache {
+    capacity: usize,
+    entries: Mutex<VecDeque<([u8; 32], Vec<u8>)>>,
+}
+
+impl ExecutionCache {
+    /// A cache of the last `capacity` outputs, disabled if `capacity` is zero.
+    pub(crate) fn new(capacity: usize) -> Self {
+        Self {
+            capacity,
+            entries: Default::default(),
+        }
+    }
+
+    /// Returns the cached output of the request identified by `key`, or runs `request` and
+    /// caches its output if it succeeds. Requests without a key always run.
+    pub(crate) fn run(
+        &self,
+        path: &SnapshotPath,
+        client: &ClientPath,
+        key: Option<Vec<u8>>,
+        request: impl FnOnce() -> Result<Vec<u8>>,
+    ) -> Result<Vec<u8>> {
+        let Some(key) = key.filter(|_| self.capacity > 0) else {
+            return request();
+        };
+        let id = id(path, client, &key);
+        {
+            let mut entries = self.entries.lock().unwrap();
+            if let Some(index) = entries.iter().position(|(entry, _)| *entry == id) {
+                let entry = entries.remove(index).unwrap();
+                let output = entry.1.clone();
+                entries.push_back(entry);
+                return Ok(output);
+            }
+        }
+
+        // Not holding the lock while proving: identical requests racing each other both run.
+        let output = request()?;
+        let mut entries = self.entries.lock().unwrap();
+        if entries.len() >= self.capacity {
+            entries.pop_front();
+        }
+        entries.push_back((id, output.clone()));
+        Ok(output)
+    }
+}
+
+fn id(path: &SnapshotPath, client: &ClientPath, key: &[u8]) -> [u8; 32] {
+    let mut data = Vec::new();
+    for part in [
+        path.as_path().as_os_str().as_encoded_bytes(),
+        client.as_ref(),
+        key,
+    ] {
+        data.extend_from_slice(&(part.len() as u64).to_le_bytes());
+        data.extend_from_slice(part);
+    }
+    let mut id = [0u8; 32];
+    SHA256(&data, &mut id);
+    id
+}
@@ -0,0 +1,124 @@
+// Copyright 2019-2023 Tauri Programme within The Commons Conservancy
+// SPDX-License-Identifier: Apache-2.0
+// SPDX-License-Identifier: MIT
+
+use std::{
+    collections::HashMap,
+    sync::{Arc, Mutex},
+    time::{Duration, Instant},
+};
+
+use crate::{
+    paths::SnapshotPath,
+    stronghold::{Error, Result},
+};
+
+/// How long the output of a completed request is returned again for the same idempotency key.
+pub const IDEMPOTENCY_TTL: Duration = Duration::from_secs(60);
+
+enum Request {
+    InFlight,
+    Done { output: Vec<u8>, at: Instant },
+}
+
+type Requests = HashMap<(SnapshotPath, String), Request>;
+
+/// Per-snapshot idempotency keys of recent requests, kept in memory only.
+#[derive(Default)]
+pub(crate) struct Idempotency {
+    requests: Arc<Mutex<Requests>>,
+}
+
+/// What [`Idempotency::begin`] found for a key.
+pub(crate) enum Begin {
+    /// The output of the request that completed with this key.
+    Done(Vec<u8>),
+    /// The request is now in flight, until [`InFlight::finish`] or the drop of the entry.
+    Started(InFlight),
+}
+
+/// The entry of a request in flight. Forgotten when dropped without [`InFlight::finish`],
+/// e.g. if the request panicked.
+pub(crate) struct InFlight {
+    requests: Arc<Mutex<Requests>>,
+    id: Option<(SnapshotPath, String)>,
+}
+
+impl InFlight {
+    /// Remembers the output of the request, or forgets it if it failed so that it can be
+    /// retried with the same key.
+    pub(crate) fn finish(mut self, result: &Result<Vec<u8>>) {
+        let id = self.id.take().unwrap();
+        let mut requests = self.requests.lock().unwrap();
+        match result {
+            Ok(output) => {
+                requests.insert(
+                    id,
+                    Request::Done {
+                        output: output.clone(),
+                        at: Instant::now(),
+                    },
+                );
+            }
+            Err(_) => {
+                requests.remove(&id);
+            }
+        }
+    }
+}
+
+impl Drop for InFlight {
+    fn drop(&mut self) {
+        if let Some(id) = self.id.take() {
+            self.requests.lock().unwrap().remove(&id);
+        }
+    }
+}
+
+impl Idempotency {
+    /// Runs `request` unless `key` was seen for `path` recently.
+    ///
+    /// A request still running for the same key fails with [`Error::DuplicateRequest`], one
+    /// that completed within [`IDEMPOTENCY_TTL`] returns its output again. Failed requests are
+    /// forgotten, so they can be retried with the same key.
+    pub(crate) fn run(
+        &self,
+        path: &SnapshotPath,
+        key: Option<String>,
+        request: impl FnOnce() -> Result<Vec<u8>>,
+    ) -> Result<Vec<u8>> {
+        let Some(key) = key else {
+            return request();
+        };
+        match self.begin(path, key)? {
+            Begin::Done(output) => Ok(output),
+            Begin::Started(in_flight) => {
+                let result = request();
+                in_flight.finish(&result);
+                result
+            }
+        }
+    }
+
+    /// [`run`](Self::run), for a request that may outlive the caller, e.g. one left running
+    /// after a timeout: the key stays in flight until the request calls [`InFlight::finish`].
+    pub(crate) fn begin(&self, path: &SnapshotPath, key: String) -> Result<Begin> {
+        let id = (path.clone(), key);
+        let mut requests = self.requests.lock().unwrap();
+        requests.retain(|_, request| match request {
+            Request::InFlight => true,
+            Request::Done { at, .. } => at.elapsed() < IDEMPOTENCY_TTL,
+        });
+        match requests.get(&id) {
+            Some(Request::InFlight) => Err(Error::DuplicateRequest(id.1)),
+            Some(Request::Done { output, .. }) => Ok(Begin::Done(output.clone())),
+            None => {
+                requests.insert(id.clone(), Request::InFlight);
+                Ok(Begin::Started(InFlight {
+                    requests: Arc::clone(&self.requests),
+                    id: Some(id),
+                }))
+            }
+        }
+    }
+}
@@ -0,0 +1,127 @@
+// Copyright 2019-2023 Tauri Programme within The Commons Conservancy
+// SPDX-License-Identifier: Apache-2.0
+// SPDX-License-Identifier: MIT
+
+//! Pre-flight checks of Aleo function inputs against the function's declared input types,
+//! and against the [`InputLimits`] of the collection.
+
+use std::fmt::Display;
+
+use snarkvm_console::{
+    network::Network,
+    program::{Plaintext, PlaintextType, Value, ValueType},
+};
+
+use crate::stronghold::{Error, Result};
+
+/// Default of [`InputLimits::max_inputs`], twice the most inputs an Aleo function takes.
+pub const DEFAULT_MAX_INPUTS: usize = 32;
+
+/// Default of [`InputLimits::max_input_size`].
+pub const DEFAULT_MAX_INPUT_SIZE: usize = 64 * 1024;
+
+/// Bounds on the Aleo inputs and records of a procedure, see
+/// [`Builder::input_limits`](crate::Builder::input_limits).
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct InputLimits {
+    /// Most inputs a single function call may take.
+    pub max_inputs: usize,
+    /// Longest an input or a record may be, in bytes of its string form.
+    pub max_input_size: usize,
+}
+
+impl Default for InputLimits {
+    fn default() -> Self {
+        Self {
+            max_inputs: DEFAULT_MAX_INPUTS,
+            max_input_size: DEFAULT_MAX_INPUT_SIZE,
+        }
+    }
+}
+
+impl InputLimits {
+    /// Checks the number of `inputs` and the size of each, reported under `field`.
+    pub fn check_inputs<N: Network>(&self, field: &str, inputs: &[Value<N>]) -> Result<()> {
+        if inputs.len() > self.max_inputs {
+            return Err(Error::InputTooLarge {
+                field: field.to_string(),
+                size: inputs.len(),
+                unit: "inputs",
+                limit: self.max_inputs,
+            });
+        }
+        inputs
+            .iter()
+            .enumerate()
+            .try_for_each(|(index, input)| self.check_size(&format!("{field}[{index}]"), input))
+    }
+
+    /// Checks the size of the string form of `value`, e.g. a record, reported under `field`.
+    pub fn check_size(&self, field: &str, value: &impl Display) -> Result<()> {
+        let size = value.to_string().len();
+        if size > self.max_input_size {
+            return Err(Error::InputTooLarge {
+                field: field.to_string(),
+                size,
+                unit: "bytes",
+                limit: self.max_input_size,
+            });
+        }
+        Ok(())
+    }
+}
+
+/// Checks arity and, as far as possible without the program, the type of every input.
+///
+/// Literals must match their declared literal type exactly; structs, arrays and records are
+/// only checked for their kind, since their layout is defined by the program.
+pub fn validate_inputs<N: Network>(inputs: &[Value<N>], types: &[ValueType<N>]) -> Result<()> {
+    if inputs.len() != types.len() {
+        return Err(Error::InputCountMismatch {
+            expected: types.len(),
+            got: inputs.len(),
+        });
+    }
+    for (index, (input, ty)) in inputs.iter().zip(types).enumerate() {
+        if !matches_type(input, ty) {
+            return Err(Error::InputTypeMismatch {
+                index,
+                expected: ty.to_string(),
+                got: describe(input),
+            });
+        }
+    }
+    Ok(())
+}
+
+fn matches_type<N: Network>(input: &Value<N>, ty: &ValueType<N>) -> bool {
+    match (input, ty) {
+        (
+            Value::Plaintext(plaintext),
+            ValueType::Constant(ty) | ValueType::Public(ty) | ValueType::Private(ty),
+        ) => matches_plaintext_type(plaintext, ty),
+        (Value::Record(_), ValueType::Record(_) | ValueType::ExternalRecord(_)) => true,
+        (Value::Future(_), ValueType::Future(_)) => true,
+        _ => false,
+    }
+}
+
+fn matches_plaintext_type<N: Network>(plaintext: &Plaintext<N>, ty: &PlaintextType<N>) -> bool {
+    match (plaintext, ty) {
+        (Plaintext::Literal(literal, ..), PlaintextType::Literal(ty)) => literal.to_type() == *ty,
+        (Plaintext::Struct(..), PlaintextType::Struct(_)) => true,
+        (Plaintext::Array(..), PlaintextType::Array(_)) => true,
+        _ => false,
+    }
+}
+
+/// Names the kind of `input` without revealing its contents.
+fn describe<N: Network>(input: &Value<N>) -> String {
+    match input {
+        Value::Plaintext(Plaintext::Literal(literal, ..)) => literal.to_type().to_string(),
+        Value::Plaintext(Plaintext::Struct(..)) => "struct".into(),
+        Value::Plaintext(Plaintext::Array(..)) => "array".into(),
+        Value::Record(_) => "record".into(),
+        Value::Future(_) => "future".into(),
+    }
+}
@@ -10,6 +10,9 @@ use std::path::Path;
 /// This is a current limitation of Stronghold.
 const HASH_LENGTH: usize = 32;
 
+/// Length of the salts returned by [`generate_salt`].
+pub const SALT_LENGTH: usize = 32;
+
 pub struct KeyDerivation {}
 
 impl KeyDerivation {
@@ -17,23 +20,59 @@ impl KeyDerivation {
     /// Salt will be generated to file [`salt_path`] or taken from it
     /// if file already exists
     pub fn argon2(password: &str, salt_path: &Path) -> Vec<u8> {
-        let mut salt = [0u8; HASH_LENGTH];
-        create_or_get_salt(&mut salt, salt_path);
-
-        argon2::hash_raw(password.as_bytes(), &salt, &Default::default())
-            .expect("Failed to generate hash for password")
+        let salt = create_or_get_salt(salt_path);
+        hash_password(password.as_bytes(), &salt)
     }
 }
 
-fn create_or_get_salt(salt: &mut [u8], salt_path: &Path) {
+/// Hashes `password` with Argon2 into the 32 bytes key Stronghold expects.
+///
+/// The same password and salt always yield the same key. The parameters are the
+/// `rust-argon2` defaults: Argon2i, version 0x13, 4 MiB of memory, 3 iterations, 1 lane.
+pub fn hash_password(password: &[u8], salt: &[u8]) -> Vec<u8> {
+    let config = argon2::Config {
+        hash_length: HASH_LENGTH as u32,
+        ..Default::default()
+    };
+    argon2::hash_raw(password, salt, &config).expect("Failed to generate hash for password")
+}
+
+/// Generates a random [`SALT_LENGTH`] bytes salt for [`hash_password`].
+pub fn generate_salt() -> Vec<u8> {
+    let mut salt = vec![0u8; SALT_LENGTH];
+    ChaCha20Rng::from_entropy().fill_bytes(&mut salt);
+    salt
+}
+
+fn create_or_get_salt(salt_path: &Path) -> Vec<u8> {
     if salt_path.is_file() {
         // Get existing salt
-        let tmp = std::fs::read(salt_path).unwrap();
-        salt.clone_from_slice(&tmp);
+        std::fs::read(salt_path).unwrap()
     } else {
-        // Generate new salt
-        let mut gen = ChaCha20Rng::from_entropy();
-        gen.fill_bytes(salt);
-        std::fs::write(salt_path, salt).expect("Failed to write salt for Stronghold")
+        let salt = generate_salt();
+        std::fs::write(salt_path, &salt).expect("Failed to write salt for Stronghold");
+        salt
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn hash_password_is_deterministic_for_a_salt() {
+        let salt = [7u8; SALT_LENGTH];
+        let key = hash_password(b"password", &salt);
+        assert_eq!(key.len(), HASH_LENGTH);
+        assert_eq!(key, hash_password(b"password", &salt));
+        assert_ne!(key, hash_password(b"Password", &salt));
+        assert_ne!(key, hash_password(b"password", &[8u8; SALT_LENGTH]));
+    }
+
+    #[test]
+    fn generate_salt_returns_distinct_salts() {
+        let salt = generate_salt();
+        assert_eq!(salt.len(), SALT_LENGTH);
+        assert_ne!(salt, generate_salt());
     }
 }
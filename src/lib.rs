@@ -12,45 +12,375 @@
 )]
 
 use std::{
-    collections::HashMap,
+    collections::{hash_map::Entry, HashMap},
     fmt,
     path::PathBuf,
-    sync::{Arc, Mutex},
-    time::Duration,
+    sync::{
+        mpsc::{self, RecvTimeoutError},
+        Arc, Mutex,
+    },
+    thread,
+    time::{Duration, SystemTime},
 };
 
-use crypto::keys::bip39::{Mnemonic, Passphrase};
+use crypto::{
+    keys::bip39::{Mnemonic, Passphrase},
+    signatures::ed25519,
+};
 use iota_stronghold::{
     procedures::{
         AleoAuthorize, AleoAuthorizeFeePrivate, AleoAuthorizeFeePublic, AleoExecute, AleoSign,
-        AleoSignRequest, BIP39Generate, BIP39Recover, Curve, Ed25519Sign, GetAleoAddress,
-        GetAleoViewKey, KeyType as StrongholdKeyType, MnemonicLanguage, PublicKey, Slip10Derive,
-        Slip10DeriveInput, Slip10Generate, StrongholdProcedure,
+        AleoSignRequest, BIP39Generate, BIP39Recover, Curve, Ed25519Sign, GenerateKey,
+        GetAleoAddress, GetAleoViewKey, KeyType as StrongholdKeyType, MnemonicLanguage, PublicKey,
+        Slip10Derive, Slip10DeriveInput, Slip10Generate, StrongholdProcedure,
     },
     Client, Location,
 };
 
-use serde::{de::Visitor, Deserialize, Deserializer, Serialize};
+use attempts::{AttemptLimit, Attempts};
+use audit::{AuditLog, AuditOperation, AuditSink};
+use codec::Codec;
+use debounce::{Debouncer, SaveDebounce};
+use derivation::{aleo_account_chain, derivation_path_from_string};
+use execution_cache::ExecutionCache;
+use idempotency::{Begin, Idempotency};
+use inputs::InputLimits;
+use output::{
+    AleoAccountKeys, AuthorizationFormat, DecryptedRecord, KeyEncoding, PublicIdentity,
+    SignatureAlgorithm, SignatureOutput,
+};
+use page::{paginate, Page, PageRequest};
+use password::Password;
+use paths::{ClientPath, RecordPath, SnapshotPath, VaultPath};
+use progress::ExecutionStage;
+use secret::SecretString;
+use serde::{
+    de::{DeserializeOwned, Visitor},
+    Deserialize, Deserializer, Serialize,
+};
+use snapshot_store::SnapshotStore;
 use snarkvm_console::{
+    account::{Address, PrivateKey, ViewKey},
     network::Network,
-    program::{Field, Identifier, Plaintext, ProgramID, Record, Value, ValueType},
+    prelude::ToBytes,
+    program::{
+        Ciphertext, Entry as RecordEntry, Field, Identifier, Literal, Plaintext, ProgramID, Record,
+        Value, ValueType,
+    },
 };
-use std::marker::PhantomData;
-use stronghold::{Error, Result, Stronghold};
-use zeroize::{Zeroize, Zeroizing};
+use std::{marker::PhantomData, str::FromStr};
+use store_key::{decode_key, encode_key, scoped_key, StoreKey};
+use stronghold::{Backend, Error, HealthReport, Result, Stronghold};
+use zeroize::Zeroizing;
 
+#[cfg(feature = "tauri")]
+pub mod app;
+pub mod attempts;
+pub mod audit;
+#[cfg(feature = "blocking")]
+pub mod blocking;
+pub mod codec;
+pub mod combine;
+pub mod debounce;
+pub mod derivation;
+pub mod display;
+pub mod domain;
+pub mod execution_cache;
+pub mod idempotency;
+pub mod inputs;
 #[cfg(feature = "kdf")]
 pub mod kdf;
+#[cfg(feature = "mlock")]
+pub mod mlock;
+pub mod output;
+pub mod page;
+pub mod password;
+pub mod paths;
+pub mod progress;
+pub mod schema;
+pub mod sealed;
+pub mod secret;
+pub mod shamir;
+pub mod snapshot_store;
+pub mod sponsor;
 
+pub mod store_key;
 pub mod stronghold;
+pub mod wordlist;
+#[cfg(feature = "worker")]
+pub mod worker;
 
 type PasswordHashFn = dyn Fn(&str) -> Vec<u8> + Send + Sync;
 
+/// Future returned by a [`HardwareKeyFn`].
+#[cfg(feature = "hardware-key")]
+pub type HardwareKeyFuture =
+    std::pin::Pin<Box<dyn std::future::Future<Output = Result<Zeroizing<Vec<u8>>>> + Send>>;
+
+/// Obtains the 32 byte Stronghold key from a hardware token, e.g. an HSM or a WebAuthn PRF.
+#[cfg(feature = "hardware-key")]
+pub type HardwareKeyFn = dyn Fn() -> HardwareKeyFuture + Send + Sync;
+
+/// Future returned by an [`UnlockHook`].
+pub type UnlockHookFuture<'a> =
+    std::pin::Pin<Box<dyn std::future::Future<Output = Result<()>> + Send + 'a>>;
+
+/// Runs after a snapshot is unlocked, see [`Builder::on_unlock`].
+pub type UnlockHook =
+    dyn for<'a> Fn(&'a StrongholdCollection, SnapshotPath) -> UnlockHookFuture<'a> + Send + Sync;
+
+/// Vault holding the secrets given to [`execute_procedure_with_ephemeral_secrets`] while
+/// their procedure runs.
+pub const EPHEMERAL_VAULT: &[u8] = b"__tauri_plugin_stronghold_ephemeral";
+
+/// Largest number of bytes a `Random` procedure returns at once.
+pub const MAX_RANDOM_BYTES: usize = 1024;
+
+/// Prefix of Aleo private keys in their string form, on every network.
+const ALEO_PRIVATE_KEY_PREFIX: &str = "APrivateKey1";
+
+/// Default upper bound, in bytes, for vault and record paths.
+pub const DEFAULT_MAX_PATH_LENGTH: usize = 256;
+
+struct Config {
+    max_path_length: usize,
+    backend: Backend,
+    file_mode: Option<u32>,
+    attempt_limit: Option<AttemptLimit>,
+    max_snapshot_size: Option<u64>,
+    temp_dir: Option<PathBuf>,
+    save_debounce: Option<SaveDebounce>,
+    execution_cache_size: usize,
+    codec: Codec,
+    snapshot_store: Option<Arc<dyn SnapshotStore>>,
+    unlock_hook: Option<Box<UnlockHook>>,
+    procedure_timeout: Option<Duration>,
+    audit_sink: Option<Box<dyn AuditSink>>,
+    input_limits: InputLimits,
+    #[cfg(feature = "mlock")]
+    lock_memory: bool,
+}
+
+impl Default for Config {
+    fn default() -> Self {
+        Self {
+            max_path_length: DEFAULT_MAX_PATH_LENGTH,
+            backend: Backend::default(),
+            file_mode: None,
+            attempt_limit: None,
+            max_snapshot_size: None,
+            temp_dir: None,
+            save_debounce: None,
+            execution_cache_size: 0,
+            codec: Codec::default(),
+            snapshot_store: None,
+            unlock_hook: None,
+            procedure_timeout: None,
+            audit_sink: None,
+            input_limits: InputLimits::default(),
+            #[cfg(feature = "mlock")]
+            lock_memory: false,
+        }
+    }
+}
+
+/// The snapshots loaded by the plugin.
+///
+/// The map is locked only to look up, insert or remove a snapshot, never while a snapshot lock
+/// is held. A snapshot lock is held while its metadata is read or changed and while it is saved;
+/// procedures and the store run on a client handle without it, except where a function says so.
+#[derive(Default)]
+pub struct StrongholdCollection {
+    /// Every snapshot has its own lock, the map is only locked to look one up, so that
+    /// operations on different snapshots don't wait for each other.
+    snapshots: Arc<Mutex<HashMap<SnapshotPath, Arc<Mutex<Stronghold>>>>>,
+    attempts: Attempts,
+    idempotency: Idempotency,
+    execution_cache: ExecutionCache,
+    /// Set when saves are debounced, see [`Builder::save_debounce`].
+    debouncer: Option<Debouncer>,
+    /// Hash functions registered through [`reinitialize_hash_function`].
+    legacy_hash_functions: Mutex<HashMap<SnapshotPath, Arc<PasswordHashFn>>>,
+    /// Set with [`Builder::audit_sink`].
+    audit_log: Option<AuditLog>,
+    config: Config,
+}
+
+impl Drop for StrongholdCollection {
+    /// Saves every loaded snapshot as a safety net for a forgotten [`save`].
+    fn drop(&mut self) {
+        let snapshots = self
+            .snapshots
+            .lock()
+            .unwrap_or_else(|poisoned| poisoned.into_inner());
+        for stronghold in snapshots.values() {
+            let stronghold = stronghold
+                .lock()
+                .unwrap_or_else(|poisoned| poisoned.into_inner());
+            if let Err(e) = stronghold.save() {
+                log::error!("save on drop failed: {e}");
+            }
+        }
+    }
+}
+
+/// Builds a [`StrongholdCollection`] with non-default settings.
 #[derive(Default)]
-pub struct StrongholdCollection(Arc<Mutex<HashMap<PathBuf, Stronghold>>>);
+pub struct Builder {
+    config: Config,
+}
+
+impl Builder {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Sets the maximum length in bytes accepted for vault and record paths.
+    pub fn max_path_length(mut self, max_path_length: usize) -> Self {
+        self.config.max_path_length = max_path_length;
+        self
+    }
+
+    /// Sets the Unix permission bits of snapshot files, e.g. `0o600`, see
+    /// [`Stronghold::with_file_mode`].
+    pub fn snapshot_file_mode(mut self, mode: u32) -> Self {
+        self.config.file_mode = Some(mode);
+        self
+    }
+
+    /// Delays unlock attempts after `limit.max_attempts` consecutive wrong passwords for a snapshot,
+    /// failing them with [`Error::TooManyAttempts`]. Disabled by default.
+    pub fn attempt_limit(mut self, limit: AttemptLimit) -> Self {
+        self.config.attempt_limit = Some(limit);
+        self
+    }
+
+    /// Fails writes and saves that would grow a snapshot file past `bytes` with
+    /// [`Error::QuotaExceeded`], see [`Stronghold::reserve`]. Disabled by default.
+    pub fn max_snapshot_size(mut self, bytes: u64) -> Self {
+        self.config.max_snapshot_size = Some(bytes);
+        self
+    }
+
+    /// Stages saves of snapshot files in `dir`, see [`Stronghold::with_temp_dir`].
+    pub fn temp_dir(mut self, dir: impl Into<PathBuf>) -> Self {
+        self.config.temp_dir = Some(dir.into());
+        self
+    }
+
+    /// Saves snapshots once `debounce.window` passed without a write, or `debounce.max_delay`
+    /// after the first unsaved one. Disabled by default.
+    pub fn save_debounce(mut self, debounce: SaveDebounce) -> Self {
+        self.config.save_debounce = Some(debounce);
+        self
+    }
+
+    /// Returns the output of an `AleoExecute` again for an identical request among the last
+    /// `capacity`, instead of proving it twice. Disabled by default.
+    ///
+    /// A cached transaction spends the same records again, so only enable it for tests.
+    pub fn execution_cache(mut self, capacity: usize) -> Self {
+        self.config.execution_cache_size = capacity;
+        self
+    }
+
+    /// Sets how [`save_typed`] and [`get_typed`] encode values. JSON by default.
+    pub fn store_codec(mut self, codec: Codec) -> Self {
+        self.config.codec = codec;
+        self
+    }
+
+    /// Locks the process memory in RAM before the first snapshot is decrypted, so secrets
+    /// can't be swapped to disk. See [`mlock`] for platform support and limits.
+    #[cfg(feature = "mlock")]
+    pub fn lock_memory(mut self) -> Self {
+        self.config.lock_memory = true;
+        self
+    }
+
+    /// Keeps every snapshot of the collection in memory, see [`Backend::Memory`].
+    #[cfg(feature = "memory-backend")]
+    pub fn in_memory(mut self) -> Self {
+        self.config.backend = Backend::Memory;
+        self.config.snapshot_store = None;
+        self
+    }
+
+    /// Persists every snapshot of the collection through `store`, see [`Backend::Store`].
+    pub fn snapshot_store(mut self, store: impl SnapshotStore + 'static) -> Self {
+        self.config.backend = Backend::Store;
+        self.config.snapshot_store = Some(Arc::new(store));
+        self
+    }
+
+    /// Runs `hook` with the snapshot after every successful unlock, e.g. to create default clients.
+    /// Its error is returned by the unlock, which leaves the snapshot loaded.
+    pub fn on_unlock(
+        mut self,
+        hook: impl for<'a> Fn(&'a StrongholdCollection, SnapshotPath) -> UnlockHookFuture<'a>
+            + Send
+            + Sync
+            + 'static,
+    ) -> Self {
+        self.config.unlock_hook = Some(Box::new(hook));
+        self
+    }
+
+    /// Fails a procedure with [`Error::Timeout`] once it ran for `timeout`, see
+    /// [`execute_procedure_with_timeout`].
+    ///
+    /// The procedure isn't interrupted: it keeps running on its own thread, and only the caller
+    /// stops waiting.
+    pub fn procedure_timeout(mut self, timeout: Duration) -> Self {
+        self.config.procedure_timeout = Some(timeout);
+        self
+    }
+
+    /// Bounds the Aleo inputs and records of a procedure, [`InputLimits::default`] otherwise.
+    /// Procedures over them fail with [`Error::InputTooLarge`].
+    pub fn input_limits(mut self, limits: InputLimits) -> Self {
+        self.config.input_limits = limits;
+        self
+    }
+
+    /// Reports every write, read and deletion of a secret and every procedure run to `sink`,
+    /// see [`audit`]. Store records and session records aren't reported.
+    pub fn audit_sink(mut self, sink: impl AuditSink + 'static) -> Self {
+        self.config.audit_sink = Some(Box::new(sink));
+        self
+    }
+
+    pub fn build(mut self) -> StrongholdCollection {
+        let snapshots: Arc<Mutex<HashMap<_, _>>> = Default::default();
+        let debouncer = self
+            .config
+            .save_debounce
+            .map(|debounce| Debouncer::start(debounce, Arc::downgrade(&snapshots)));
+        StrongholdCollection {
+            snapshots,
+            attempts: Attempts::new(self.config.attempt_limit),
+            idempotency: Default::default(),
+            execution_cache: ExecutionCache::new(self.config.execution_cache_size),
+            debouncer,
+            legacy_hash_functions: Default::default(),
+            audit_log: self.config.audit_sink.take().map(AuditLog::new),
+            config: self.config,
+        }
+    }
+}
 
 pub struct PasswordHashFunction(pub Box<PasswordHashFn>);
 
+/// What [`peek_store_record`] and [`list_store_records`] tell about a store record.
+#[derive(Serialize, Clone, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct StoreRecordMeta {
+    /// Length of the value in bytes.
+    pub len: usize,
+    /// Time left before the record expires. `None` if it was saved without a lifetime.
+    pub expires_in: Option<Duration>,
+}
+
 #[derive(Deserialize, Hash, Eq, PartialEq, Ord, PartialOrd, Serialize, Clone)]
 #[serde(untagged)]
 pub enum BytesDto {
@@ -76,13 +406,25 @@ impl From<BytesDto> for Vec<u8> {
     }
 }
 
-#[derive(Deserialize)]
+#[derive(Deserialize, Serialize, Clone)]
 #[serde(tag = "type", content = "payload")]
 pub enum LocationDto {
     Generic { vault: BytesDto, record: BytesDto },
     Counter { vault: BytesDto, counter: usize },
 }
 
+impl LocationDto {
+    fn validate(&self, max_path_length: usize) -> Result<()> {
+        match self {
+            LocationDto::Generic { vault, record } => {
+                validate_path("vault", vault, max_path_length)?;
+                validate_path("record", record, max_path_length)
+            }
+            LocationDto::Counter { vault, .. } => validate_path("vault", vault, max_path_length),
+        }
+    }
+}
+
 impl From<LocationDto> for Location {
     fn from(dto: LocationDto) -> Location {
         match dto {
@@ -92,7 +434,28 @@ impl From<LocationDto> for Location {
     }
 }
 
-#[derive(Deserialize)]
+impl From<Location> for LocationDto {
+    fn from(location: Location) -> LocationDto {
+        match location {
+            Location::Generic {
+                vault_path,
+                record_path,
+            } => LocationDto::Generic {
+                vault: BytesDto::Raw(vault_path),
+                record: BytesDto::Raw(record_path),
+            },
+            Location::Counter {
+                vault_path,
+                counter,
+            } => LocationDto::Counter {
+                vault: BytesDto::Raw(vault_path),
+                counter,
+            },
+        }
+    }
+}
+
+#[derive(Deserialize, Serialize, Clone)]
 #[serde(tag = "type", content = "payload")]
 #[allow(clippy::upper_case_acronyms)]
 pub enum Slip10DeriveInputDto {
@@ -100,6 +463,15 @@ pub enum Slip10DeriveInputDto {
     Key(LocationDto),
 }
 
+/// The public key a [`ProcedureDto::VerifyEd25519`] checks against: its 32 raw bytes, or the
+/// location of the private key it belongs to.
+#[derive(Deserialize, Serialize, Clone)]
+#[serde(tag = "type", content = "payload")]
+pub enum Ed25519PublicKeyDto {
+    Bytes(Vec<u8>),
+    Location(LocationDto),
+}
+
 impl From<Slip10DeriveInputDto> for Slip10DeriveInput {
     fn from(dto: Slip10DeriveInputDto) -> Slip10DeriveInput {
         match dto {
@@ -109,6 +481,16 @@ impl From<Slip10DeriveInputDto> for Slip10DeriveInput {
     }
 }
 
+impl From<Slip10DeriveInput> for Slip10DeriveInputDto {
+    fn from(input: Slip10DeriveInput) -> Slip10DeriveInputDto {
+        match input {
+            Slip10DeriveInput::Seed(location) => Slip10DeriveInputDto::Seed(location.into()),
+            Slip10DeriveInput::Key(location) => Slip10DeriveInputDto::Key(location.into()),
+        }
+    }
+}
+
+#[derive(Clone, Copy)]
 pub enum KeyType {
     Ed25519,
     X25519,
@@ -153,6 +535,38 @@ impl<'de> Deserialize<'de> for KeyType {
     }
 }
 
+/// How an `AleoExecute` pays its fee.
+#[derive(Deserialize)]
+#[serde(tag = "type", content = "payload", bound = "N: Network")]
+pub enum FeeSource<N: Network> {
+    /// From the public balance of the executing account.
+    Public,
+    /// By spending a `credits.aleo` record, which must hold at least the base and priority fees.
+    Private(Record<N, Plaintext<N>>),
+}
+
+impl<N: Network> FeeSource<N> {
+    fn record(&self) -> Option<&Record<N, Plaintext<N>>> {
+        match self {
+            FeeSource::Public => None,
+            FeeSource::Private(record) => Some(record),
+        }
+    }
+}
+
+/// A transition of an `AleoSignRequests`, signed like an `AleoSignRequest`.
+#[derive(Deserialize)]
+#[serde(bound = "N: Network")]
+pub struct TransitionDto<N: Network> {
+    pub program_id: ProgramID<N>,
+    pub function_name: Identifier<N>,
+    pub inputs: Vec<Value<N>>,
+    pub input_types: Vec<ValueType<N>>,
+    /// Whether this is the function called by the user, whose `tvk` the others are chained to.
+    #[serde(default)]
+    pub is_root: bool,
+}
+
 #[derive(Deserialize)]
 #[serde(tag = "type", content = "payload", bound = "N: Network")]
 #[allow(clippy::upper_case_acronyms)]
@@ -169,32 +583,191 @@ pub enum ProcedureDto<N: Network> {
         output: LocationDto,
         network: String,
     },
+    /// [`ProcedureDto::SLIP10Derive`] with the chain given as a path string like `m/44'/0'/0'/0/0`.
+    SLIP10DerivePath {
+        curve: Curve,
+        path: String,
+        input: Slip10DeriveInputDto,
+        output: LocationDto,
+        network: String,
+    },
+    /// Derives a new key from the secret at `input` into `output`, then deletes `input`. Returns
+    /// the chain code like [`ProcedureDto::SLIP10Derive`].
+    ///
+    /// `input` must be a generic location distinct from `output`.
+    SLIP10Rotate {
+        curve: Curve,
+        chain: Vec<u32>,
+        input: Slip10DeriveInputDto,
+        output: LocationDto,
+        network: String,
+    },
+    /// Derives the key at the BIP44 `path`, like `m/44'/0'/0'/0/0`, from the seed at `seed` and
+    /// returns its public key of type `ty` as a UTF-8 string in `encoding`, hex by default.
+    ///
+    /// The private key is staged in the [`EPHEMERAL_VAULT`] under the lock of the snapshot.
+    SLIP10DeriveString {
+        curve: Curve,
+        path: String,
+        seed: LocationDto,
+        network: String,
+        #[serde(rename = "type")]
+        ty: KeyType,
+        encoding: Option<KeyEncoding>,
+    },
+    /// Derives the Aleo account `index` from the seed at `seed`, along
+    /// [`derivation::aleo_account_chain`], and returns its [`output::AleoAccountKeys`], with the
+    /// address for `ext` if given. Staged like [`ProcedureDto::SLIP10DeriveString`].
+    DeriveViewKeyFromSeed {
+        curve: Curve,
+        seed: LocationDto,
+        index: u32,
+        network: String,
+        ext: Option<Identifier<N>>,
+    },
     BIP39Recover {
-        mnemonic: String,
-        passphrase: Option<String>,
+        mnemonic: SecretString,
+        passphrase: Option<SecretString>,
         output: LocationDto,
+        /// Custom 2048-word list used to validate the mnemonic instead of the built-in language.
+        wordlist: Option<Vec<String>>,
     },
     BIP39Generate {
-        passphrase: Option<String>,
+        passphrase: Option<SecretString>,
+        output: LocationDto,
+        /// Custom 2048-word list used to generate the mnemonic instead of the built-in language.
+        wordlist: Option<Vec<String>>,
+    },
+    /// Derives the seed of `mnemonic` with `new_passphrase` into `output`, if `mnemonic` with
+    /// `old_passphrase` yields the seed at `seed`. The seed at `seed` is kept.
+    ///
+    /// Both seeds are read into the plugin to compare them and wiped afterwards.
+    RotateBIP39Passphrase {
+        mnemonic: SecretString,
+        seed: LocationDto,
+        #[serde(rename = "oldPassphrase")]
+        old_passphrase: Option<SecretString>,
+        #[serde(rename = "newPassphrase")]
+        new_passphrase: Option<SecretString>,
+        output: LocationDto,
+    },
+    /// Returns the BIP39 seed of `mnemonic` with `passphrase` without storing it. The words aren't
+    /// checked against a wordlist.
+    ///
+    /// The caller should wipe the seed, e.g. with [`Zeroizing`].
+    #[cfg(feature = "unsafe-export")]
+    ConvertMnemonicToSeed {
+        mnemonic: SecretString,
+        passphrase: Option<SecretString>,
+    },
+    /// Returns an [`output::MnemonicValidation`] naming the first word of `mnemonic` that isn't in
+    /// the wordlist of `language`, English by default.
+    ValidateMnemonic {
+        mnemonic: SecretString,
+        language: Option<String>,
+    },
+    /// Returns `size_bytes` random bytes from the CSPRNG of the runtime, for nonces and
+    /// salts. At most [`MAX_RANDOM_BYTES`] can be requested at once.
+    Random {
+        #[serde(rename = "sizeBytes")]
+        size_bytes: usize,
+    },
+    /// Generates a standalone private key of type `ty`, without deriving it from a seed.
+    GenerateKey {
+        #[serde(rename = "type")]
+        ty: KeyType,
+        output: LocationDto,
+        /// Return the public key of the generated key instead of an empty output.
+        #[serde(rename = "returnPublicKey", default)]
+        return_public_key: bool,
+    },
+    /// Splits the secret at `seed` into `shares` Shamir shares, any `threshold` of which rebuild
+    /// it, see [`shamir`]. Returns them as a JSON list of hex strings.
+    ShamirSplit {
+        seed: LocationDto,
+        threshold: u8,
+        shares: u8,
+    },
+    /// Rebuilds a secret from the hex encoded `shares` of [`ProcedureDto::ShamirSplit`] into
+    /// `output`.
+    ShamirCombine {
+        shares: Vec<SecretString>,
+        output: LocationDto,
+    },
+    /// Imports the Aleo private key `key`, in its `APrivateKey1...` form, to `output`. Fails with
+    /// [`Error::RecordExists`] if `output` already holds a secret.
+    ImportAleoPrivateKey {
+        key: SecretString,
         output: LocationDto,
     },
+    /// Generates an Ed25519 key at `output` and returns its raw 32 byte public key. Fails with
+    /// [`Error::RecordExists`] if `output` already holds a secret.
+    GenerateEd25519AndRegister { output: LocationDto },
     PublicKey {
         #[serde(rename = "type")]
         ty: KeyType,
         #[serde(rename = "privateKey")]
         private_key: LocationDto,
     },
+    /// [`ProcedureDto::PublicKey`] returning the key as a UTF-8 string in `encoding`, hex by
+    /// default.
+    GetPublicKeyString {
+        #[serde(rename = "type")]
+        ty: KeyType,
+        #[serde(rename = "privateKey")]
+        private_key: LocationDto,
+        encoding: Option<KeyEncoding>,
+    },
+    /// Returns the public identifiers of the given private keys in an [`output::PublicIdentity`].
+    /// At least one location must be given.
+    GetPublicIdentity {
+        ed25519: Option<LocationDto>,
+        x25519: Option<LocationDto>,
+        aleo: Option<(LocationDto, Identifier<N>)>,
+        encoding: Option<KeyEncoding>,
+    },
     Ed25519Sign {
         #[serde(rename = "privateKey")]
         private_key: LocationDto,
         msg: String,
     },
     AleoSign {
+        #[serde(rename = "privateKey")]
+        private_key: LocationDto,
+        msg: String,
+        /// Domain separator, see [`domain`] for how to choose it.
+        ext: Identifier<N>,
+    },
+    /// [`ProcedureDto::Ed25519Sign`] returning a JSON [`output::SignatureOutput`] with the public key.
+    Ed25519SignStructured {
+        #[serde(rename = "privateKey")]
+        private_key: LocationDto,
+        msg: String,
+    },
+    /// Checks the raw 64 byte Ed25519 `signature` of `msg`, returning JSON `true` or `false`.
+    VerifyEd25519 {
+        #[serde(rename = "publicKey")]
+        public_key: Ed25519PublicKeyDto,
+        msg: BytesDto,
+        signature: Vec<u8>,
+    },
+    /// [`ProcedureDto::AleoSign`] returning a JSON [`output::SignatureOutput`] with the address.
+    AleoSignStructured {
         #[serde(rename = "privateKey")]
         private_key: LocationDto,
         msg: String,
         ext: Identifier<N>,
     },
+    /// Signs every message of `messages` with the key at `private_key`, returning a JSON list of
+    /// the hex encoded signatures. `ext` is required with [`SignatureAlgorithm::Aleo`] and ignored
+    /// for Ed25519.
+    SignMany {
+        algorithm: SignatureAlgorithm,
+        #[serde(rename = "privateKey")]
+        private_key: LocationDto,
+        messages: Vec<BytesDto>,
+        ext: Option<Identifier<N>>,
+    },
     GetAleoAddress {
         #[serde(rename = "privateKey")]
         private_key: LocationDto,
@@ -205,6 +778,15 @@ pub enum ProcedureDto<N: Network> {
         private_key: LocationDto,
         _network: PhantomData<N>,
     },
+    /// Decrypts the `ciphertexts` owned by the account of `private_key`, returning a JSON list of
+    /// [`output::DecryptedRecord`]. An owned one that fails to decrypt fails the scan.
+    ///
+    /// The view key is read into the plugin and isn't wiped.
+    AleoScanRecords {
+        #[serde(rename = "privateKey")]
+        private_key: LocationDto,
+        ciphertexts: Vec<Record<N, Ciphertext<N>>>,
+    },
     AleoSignRequest {
         program_id: ProgramID<N>,
         function_name: Identifier<N>,
@@ -215,17 +797,29 @@ pub enum ProcedureDto<N: Network> {
         #[serde(rename = "privateKey")]
         private_key: LocationDto,
     },
+    /// Signs the requests of every transition of one execution with the key at `private_key`,
+    /// returning a JSON list of the requests in the order of `transitions`. Exactly one transition
+    /// must be the root.
+    AleoSignRequests {
+        transitions: Vec<TransitionDto<N>>,
+        #[serde(rename = "privateKey")]
+        private_key: LocationDto,
+    },
     AleoAuthorize {
         private_key: LocationDto,
         program_id: ProgramID<N>,
         function_name: Identifier<N>,
         inputs: Vec<Value<N>>,
+        /// Declared input types of `function_name`, to check `inputs` against before authorizing.
+        input_types: Option<Vec<ValueType<N>>>,
+        format: Option<AuthorizationFormat>,
     },
     AleoAuthorizeFeePublic {
         private_key: LocationDto,
         base_fee_in_microcredits: u64,
         priority_fee_in_microcredits: u64,
         deployment_or_execution_id: Field<N>,
+        format: Option<AuthorizationFormat>,
     },
     AleoAuthorizeFeePrivate {
         private_key: LocationDto,
@@ -233,6 +827,13 @@ pub enum ProcedureDto<N: Network> {
         base_fee_in_microcredits: u64,
         priority_fee_in_microcredits: u64,
         deployment_or_execution_id: Field<N>,
+        format: Option<AuthorizationFormat>,
+    },
+    /// Checks that `fee_authorization` pays for `authorization`, returning both in an
+    /// [`output::SponsoredAuthorization`], see [`sponsor`]. No key of the vault is used.
+    AleoCombineAuthorizations {
+        authorization: String,
+        fee_authorization: String,
     },
     AleoExecute {
         #[serde(rename = "privateKey")]
@@ -240,15 +841,373 @@ pub enum ProcedureDto<N: Network> {
         program_id: ProgramID<N>,
         function_name: Identifier<N>,
         inputs: Vec<Value<N>>,
-        fee_record: Option<Record<N, Plaintext<N>>>,
+        fee: FeeSource<N>,
         priority_fee_in_microcredits: u64,
+        /// Expected base fee, used to check that a private fee record covers it.
+        base_fee_in_microcredits: Option<u64>,
+        /// The runtime fetches `program_id`, the programs it imports and the ledger state from
+        /// here.
         base_url: String,
+        /// Declared input types of `function_name`, to check `inputs` against before executing.
+        input_types: Option<Vec<ValueType<N>>>,
+        /// Repeating a key within [`idempotency::IDEMPOTENCY_TTL`] returns the first transaction
+        /// instead of creating a second one, or fails while the first is still being proven.
+        idempotency_key: Option<String>,
     },
 }
 
-impl<N: Network> From<ProcedureDto<N>> for StrongholdProcedure<N> {
-    fn from(dto: ProcedureDto<N>) -> StrongholdProcedure<N> {
-        match dto {
+/// Names of the procedures, as given in the `type` field of a [`ProcedureDto`]. See
+/// [`schema::PROCEDURE_SCHEMAS`] for their payloads.
+pub const PROCEDURE_NAMES: &[&str] = &[
+    "SLIP10Generate",
+    "SLIP10Derive",
+    "SLIP10DerivePath",
+    "SLIP10Rotate",
+    "SLIP10DeriveString",
+    "DeriveViewKeyFromSeed",
+    "BIP39Recover",
+    "BIP39Generate",
+    "RotateBIP39Passphrase",
+    #[cfg(feature = "unsafe-export")]
+    "ConvertMnemonicToSeed",
+    "ValidateMnemonic",
+    "Random",
+    "GenerateKey",
+    "GenerateEd25519AndRegister",
+    "ShamirSplit",
+    "ShamirCombine",
+    "ImportAleoPrivateKey",
+    "PublicKey",
+    "GetPublicKeyString",
+    "GetPublicIdentity",
+    "Ed25519Sign",
+    "AleoSign",
+    "Ed25519SignStructured",
+    "VerifyEd25519",
+    "AleoSignStructured",
+    "SignMany",
+    "GetAleoAddress",
+    "GetAleoViewKey",
+    "AleoScanRecords",
+    "AleoSignRequest",
+    "AleoSignRequests",
+    "AleoAuthorize",
+    "AleoAuthorizeFeePublic",
+    "AleoAuthorizeFeePrivate",
+    "AleoCombineAuthorizations",
+    "AleoExecute",
+];
+
+/// Whether the procedure `name` can run on network `N`, so that a frontend can hide the ones
+/// that can't instead of letting them fail.
+pub fn procedure_supported<N: Network>(name: &str) -> bool {
+    ProcedureDto::<N>::is_supported(name)
+}
+
+/// Every procedure with the JSON fields of its payload, e.g. to validate requests before
+/// sending them.
+pub fn procedure_schemas() -> &'static [schema::ProcedureSchema] {
+    schema::PROCEDURE_SCHEMAS
+}
+
+impl<N: Network> ProcedureDto<N> {
+    /// See [`procedure_supported`].
+    pub fn is_supported(name: &str) -> bool {
+        PROCEDURE_NAMES.contains(&name)
+    }
+
+    /// The name of the procedure, as listed in [`PROCEDURE_NAMES`].
+    pub fn name(&self) -> &'static str {
+        match self {
+            ProcedureDto::SLIP10Generate { .. } => "SLIP10Generate",
+            ProcedureDto::SLIP10Derive { .. } => "SLIP10Derive",
+            ProcedureDto::SLIP10DerivePath { .. } => "SLIP10DerivePath",
+            ProcedureDto::SLIP10Rotate { .. } => "SLIP10Rotate",
+            ProcedureDto::SLIP10DeriveString { .. } => "SLIP10DeriveString",
+            ProcedureDto::DeriveViewKeyFromSeed { .. } => "DeriveViewKeyFromSeed",
+            ProcedureDto::BIP39Recover { .. } => "BIP39Recover",
+            ProcedureDto::BIP39Generate { .. } => "BIP39Generate",
+            ProcedureDto::RotateBIP39Passphrase { .. } => "RotateBIP39Passphrase",
+            #[cfg(feature = "unsafe-export")]
+            ProcedureDto::ConvertMnemonicToSeed { .. } => "ConvertMnemonicToSeed",
+            ProcedureDto::ValidateMnemonic { .. } => "ValidateMnemonic",
+            ProcedureDto::Random { .. } => "Random",
+            ProcedureDto::GenerateKey { .. } => "GenerateKey",
+            ProcedureDto::GenerateEd25519AndRegister { .. } => "GenerateEd25519AndRegister",
+            ProcedureDto::ShamirSplit { .. } => "ShamirSplit",
+            ProcedureDto::ShamirCombine { .. } => "ShamirCombine",
+            ProcedureDto::ImportAleoPrivateKey { .. } => "ImportAleoPrivateKey",
+            ProcedureDto::PublicKey { .. } => "PublicKey",
+            ProcedureDto::GetPublicKeyString { .. } => "GetPublicKeyString",
+            ProcedureDto::GetPublicIdentity { .. } => "GetPublicIdentity",
+            ProcedureDto::Ed25519Sign { .. } => "Ed25519Sign",
+            ProcedureDto::AleoSign { .. } => "AleoSign",
+            ProcedureDto::Ed25519SignStructured { .. } => "Ed25519SignStructured",
+            ProcedureDto::VerifyEd25519 { .. } => "VerifyEd25519",
+            ProcedureDto::AleoSignStructured { .. } => "AleoSignStructured",
+            ProcedureDto::SignMany { .. } => "SignMany",
+            ProcedureDto::GetAleoAddress { .. } => "GetAleoAddress",
+            ProcedureDto::GetAleoViewKey { .. } => "GetAleoViewKey",
+            ProcedureDto::AleoScanRecords { .. } => "AleoScanRecords",
+            ProcedureDto::AleoSignRequest { .. } => "AleoSignRequest",
+            ProcedureDto::AleoSignRequests { .. } => "AleoSignRequests",
+            ProcedureDto::AleoAuthorize { .. } => "AleoAuthorize",
+            ProcedureDto::AleoAuthorizeFeePublic { .. } => "AleoAuthorizeFeePublic",
+            ProcedureDto::AleoAuthorizeFeePrivate { .. } => "AleoAuthorizeFeePrivate",
+            ProcedureDto::AleoCombineAuthorizations { .. } => "AleoCombineAuthorizations",
+            ProcedureDto::AleoExecute { .. } => "AleoExecute",
+        }
+    }
+
+    fn authorization_format(&self) -> AuthorizationFormat {
+        match self {
+            ProcedureDto::AleoAuthorize { format, .. }
+            | ProcedureDto::AleoAuthorizeFeePublic { format, .. }
+            | ProcedureDto::AleoAuthorizeFeePrivate { format, .. } => format.unwrap_or_default(),
+            _ => AuthorizationFormat::Raw,
+        }
+    }
+
+    /// Deserializes a procedure, reporting a malformed field as [`Error::InvalidInput`] named by
+    /// its path, e.g. `payload.inputs[1]`.
+    pub fn from_json(value: serde_json::Value) -> Result<Self> {
+        serde_path_to_error::deserialize(value)
+            .map_err(|e| Error::invalid_value(e.path().to_string(), e.inner().to_string()))
+    }
+
+    fn validate(&self, config: &Config) -> Result<()> {
+        for location in self.locations() {
+            location.validate(config.max_path_length)?;
+        }
+        self.check_input_limits(&config.input_limits)?;
+        match self {
+            ProcedureDto::AleoSign { ext, .. }
+            | ProcedureDto::AleoSignStructured { ext, .. }
+            | ProcedureDto::SignMany {
+                algorithm: SignatureAlgorithm::Aleo,
+                ext: Some(ext),
+                ..
+            } => domain::validate_ext(ext),
+            ProcedureDto::SignMany {
+                algorithm: SignatureAlgorithm::Aleo,
+                ext: None,
+                ..
+            } => Err(Error::invalid_input("ext", "is required to sign with Aleo")),
+            ProcedureDto::SLIP10DeriveString { path, .. } => {
+                derivation_path_from_string(path).map(drop)
+            }
+            ProcedureDto::AleoSignRequests { transitions, .. } => {
+                let roots = transitions.iter().filter(|t| t.is_root).count();
+                if roots != 1 {
+                    return Err(Error::invalid_input(
+                        "transitions",
+                        format!("exactly one must be the root, {roots} are"),
+                    ));
+                }
+                transitions.iter().enumerate().try_for_each(|(index, t)| {
+                    inputs::validate_inputs(&t.inputs, &t.input_types).map_err(|e| {
+                        Error::invalid_input(format!("transitions[{index}]"), e.to_string())
+                    })
+                })
+            }
+            ProcedureDto::DeriveViewKeyFromSeed { index, .. } => {
+                aleo_account_chain(*index).map(drop)
+            }
+            ProcedureDto::ImportAleoPrivateKey { key, .. }
+                if !key.as_str().starts_with(ALEO_PRIVATE_KEY_PREFIX) =>
+            {
+                Err(Error::invalid_input(
+                    "key",
+                    format!(
+                        "must be an Aleo private key starting with `{ALEO_PRIVATE_KEY_PREFIX}`"
+                    ),
+                ))
+            }
+            ProcedureDto::Random { size_bytes } if !(1..=MAX_RANDOM_BYTES).contains(size_bytes) => {
+                Err(Error::invalid_input(
+                    "sizeBytes",
+                    format!("must be between 1 and {MAX_RANDOM_BYTES}"),
+                ))
+            }
+            _ => Ok(()),
+        }
+    }
+
+    /// Bounds the Aleo inputs and records before snarkVM processes them.
+    fn check_input_limits(&self, limits: &InputLimits) -> Result<()> {
+        match self {
+            ProcedureDto::AleoSignRequest { inputs, .. }
+            | ProcedureDto::AleoAuthorize { inputs, .. } => limits.check_inputs("inputs", inputs),
+            ProcedureDto::AleoExecute { inputs, fee, .. } => {
+                limits.check_inputs("inputs", inputs)?;
+                fee.record()
+                    .map_or(Ok(()), |record| limits.check_size("fee", record))
+            }
+            ProcedureDto::AleoSignRequests { transitions, .. } => {
+                transitions.iter().enumerate().try_for_each(|(index, t)| {
+                    limits.check_inputs(&format!("transitions[{index}].inputs"), &t.inputs)
+                })
+            }
+            ProcedureDto::AleoAuthorizeFeePrivate { credits, .. } => {
+                limits.check_size("credits", credits)
+            }
+            ProcedureDto::AleoScanRecords { ciphertexts, .. } => ciphertexts
+                .iter()
+                .enumerate()
+                .try_for_each(|(index, ciphertext)| {
+                    limits.check_size(&format!("ciphertexts[{index}]"), ciphertext)
+                }),
+            _ => Ok(()),
+        }
+    }
+
+    /// Whether the procedure runs under the lock of the snapshot, see [`run_procedure_locked`].
+    fn runs_locked(&self) -> bool {
+        match self {
+            ProcedureDto::SLIP10DeriveString { .. }
+            | ProcedureDto::DeriveViewKeyFromSeed { .. }
+            | ProcedureDto::RotateBIP39Passphrase { .. }
+            | ProcedureDto::ImportAleoPrivateKey { .. }
+            | ProcedureDto::GenerateEd25519AndRegister { .. } => true,
+            #[cfg(feature = "unsafe-export")]
+            ProcedureDto::ConvertMnemonicToSeed { .. } => true,
+            _ => false,
+        }
+    }
+
+    fn idempotency_key(&self) -> Option<String> {
+        match self {
+            ProcedureDto::AleoExecute {
+                idempotency_key, ..
+            } => idempotency_key.clone(),
+            _ => None,
+        }
+    }
+
+    /// What identifies an `AleoExecute` for the [`Builder::execution_cache`]: every field of it.
+    fn execution_cache_key(&self) -> Option<Vec<u8>> {
+        match self {
+            ProcedureDto::AleoExecute {
+                private_key,
+                program_id,
+                function_name,
+                inputs,
+                fee,
+                priority_fee_in_microcredits,
+                base_fee_in_microcredits,
+                base_url,
+                input_types,
+                idempotency_key,
+            } => serde_json::to_vec(&(
+                private_key,
+                program_id.to_string(),
+                function_name.to_string(),
+                inputs.iter().map(ToString::to_string).collect::<Vec<_>>(),
+                fee.record().map(ToString::to_string),
+                priority_fee_in_microcredits,
+                base_fee_in_microcredits,
+                base_url,
+                input_types
+                    .as_ref()
+                    .map(|types| types.iter().map(ToString::to_string).collect::<Vec<_>>()),
+                idempotency_key,
+            ))
+            .ok(),
+            _ => None,
+        }
+    }
+
+    /// Locations this procedure writes a secret to, with `true`, or deletes it from.
+    fn record_changes(&self) -> Vec<(LocationDto, bool)> {
+        match self {
+            ProcedureDto::SLIP10Generate { output, .. }
+            | ProcedureDto::SLIP10Derive { output, .. }
+            | ProcedureDto::SLIP10DerivePath { output, .. }
+            | ProcedureDto::BIP39Recover { output, .. }
+            | ProcedureDto::BIP39Generate { output, .. }
+            | ProcedureDto::RotateBIP39Passphrase { output, .. }
+            | ProcedureDto::GenerateKey { output, .. }
+            | ProcedureDto::GenerateEd25519AndRegister { output }
+            | ProcedureDto::ShamirCombine { output, .. }
+            | ProcedureDto::ImportAleoPrivateKey { output, .. } => vec![(output.clone(), true)],
+            ProcedureDto::SLIP10Rotate { input, output, .. } => match input {
+                Slip10DeriveInputDto::Seed(input) | Slip10DeriveInputDto::Key(input) => {
+                    vec![(output.clone(), true), (input.clone(), false)]
+                }
+            },
+            _ => Vec::new(),
+        }
+    }
+
+    /// Vault locations read or written by this procedure.
+    fn locations(&self) -> Vec<&LocationDto> {
+        match self {
+            ProcedureDto::SLIP10Generate { output, .. }
+            | ProcedureDto::BIP39Recover { output, .. }
+            | ProcedureDto::BIP39Generate { output, .. }
+            | ProcedureDto::GenerateKey { output, .. }
+            | ProcedureDto::GenerateEd25519AndRegister { output }
+            | ProcedureDto::ShamirCombine { output, .. }
+            | ProcedureDto::ImportAleoPrivateKey { output, .. } => vec![output],
+            ProcedureDto::RotateBIP39Passphrase { seed, output, .. } => vec![seed, output],
+            ProcedureDto::ShamirSplit { seed, .. }
+            | ProcedureDto::SLIP10DeriveString { seed, .. }
+            | ProcedureDto::DeriveViewKeyFromSeed { seed, .. } => vec![seed],
+            ProcedureDto::ValidateMnemonic { .. }
+            | ProcedureDto::Random { .. }
+            | ProcedureDto::AleoCombineAuthorizations { .. }
+            | ProcedureDto::VerifyEd25519 {
+                public_key: Ed25519PublicKeyDto::Bytes(_),
+                ..
+            } => Vec::new(),
+            ProcedureDto::VerifyEd25519 {
+                public_key: Ed25519PublicKeyDto::Location(location),
+                ..
+            } => vec![location],
+            #[cfg(feature = "unsafe-export")]
+            ProcedureDto::ConvertMnemonicToSeed { .. } => Vec::new(),
+            ProcedureDto::GetPublicIdentity {
+                ed25519,
+                x25519,
+                aleo,
+                ..
+            } => ed25519
+                .iter()
+                .chain(x25519)
+                .chain(aleo.iter().map(|(location, _)| location))
+                .collect(),
+            ProcedureDto::SLIP10Derive { input, output, .. }
+            | ProcedureDto::SLIP10DerivePath { input, output, .. }
+            | ProcedureDto::SLIP10Rotate { input, output, .. } => match input {
+                Slip10DeriveInputDto::Seed(input) | Slip10DeriveInputDto::Key(input) => {
+                    vec![input, output]
+                }
+            },
+            ProcedureDto::PublicKey { private_key, .. }
+            | ProcedureDto::GetPublicKeyString { private_key, .. }
+            | ProcedureDto::Ed25519Sign { private_key, .. }
+            | ProcedureDto::AleoSign { private_key, .. }
+            | ProcedureDto::Ed25519SignStructured { private_key, .. }
+            | ProcedureDto::AleoSignStructured { private_key, .. }
+            | ProcedureDto::SignMany { private_key, .. }
+            | ProcedureDto::GetAleoAddress { private_key, .. }
+            | ProcedureDto::GetAleoViewKey { private_key, .. }
+            | ProcedureDto::AleoScanRecords { private_key, .. }
+            | ProcedureDto::AleoSignRequest { private_key, .. }
+            | ProcedureDto::AleoSignRequests { private_key, .. }
+            | ProcedureDto::AleoAuthorize { private_key, .. }
+            | ProcedureDto::AleoAuthorizeFeePublic { private_key, .. }
+            | ProcedureDto::AleoAuthorizeFeePrivate { private_key, .. }
+            | ProcedureDto::AleoExecute { private_key, .. } => vec![private_key],
+        }
+    }
+}
+
+impl<N: Network> TryFrom<ProcedureDto<N>> for StrongholdProcedure<N> {
+    type Error = Error;
+
+    fn try_from(dto: ProcedureDto<N>) -> Result<StrongholdProcedure<N>> {
+        let procedure = match dto {
             ProcedureDto::SLIP10Generate { output, size_bytes } => {
                 StrongholdProcedure::Slip10Generate(Slip10Generate {
                     output: output.into(),
@@ -268,32 +1227,107 @@ impl<N: Network> From<ProcedureDto<N>> for StrongholdProcedure<N> {
                 output: output.into(),
                 network,
             }),
+            ProcedureDto::SLIP10DerivePath {
+                curve,
+                path,
+                input,
+                output,
+                network,
+            } => StrongholdProcedure::Slip10Derive(Slip10Derive {
+                curve,
+                chain: derivation_path_from_string(&path)?,
+                input: input.into(),
+                output: output.into(),
+                network,
+            }),
+            ProcedureDto::SLIP10Rotate { .. } => {
+                return Err(Error::CompositeProcedure("SLIP10Rotate"))
+            }
+            ProcedureDto::SLIP10DeriveString { .. } => {
+                return Err(Error::CompositeProcedure("SLIP10DeriveString"))
+            }
             ProcedureDto::BIP39Recover {
                 mnemonic,
                 passphrase,
                 output,
-            } => StrongholdProcedure::BIP39Recover(BIP39Recover {
-                mnemonic: Mnemonic::from(mnemonic),
-                passphrase: Passphrase::from(passphrase.unwrap_or("".to_string())),
-                output: output.into(),
-            }),
-            ProcedureDto::BIP39Generate { passphrase, output } => {
-                StrongholdProcedure::BIP39Generate(BIP39Generate {
-                    passphrase: Passphrase::from(passphrase.unwrap_or("".to_string())),
+                wordlist,
+            } => {
+                let mnemonic = Mnemonic::from(mnemonic.into_inner());
+                if let Some(words) = wordlist {
+                    wordlist::verify(&mnemonic, &words)?;
+                }
+                StrongholdProcedure::BIP39Recover(BIP39Recover {
+                    mnemonic,
+                    passphrase: passphrase_from(passphrase),
                     output: output.into(),
-                    language: MnemonicLanguage::English,
                 })
             }
-            ProcedureDto::PublicKey { ty, private_key } => {
-                StrongholdProcedure::PublicKey(PublicKey {
-                    ty: ty.into(),
-                    private_key: private_key.into(),
-                })
+            ProcedureDto::BIP39Generate {
+                wordlist: Some(_), ..
+            } => return Err(Error::CompositeProcedure("BIP39Generate")),
+            ProcedureDto::RotateBIP39Passphrase { .. } => {
+                return Err(Error::CompositeProcedure("RotateBIP39Passphrase"))
             }
-            ProcedureDto::Ed25519Sign { private_key, msg } => {
-                StrongholdProcedure::Ed25519Sign(Ed25519Sign {
+            ProcedureDto::BIP39Generate {
+                passphrase,
+                output,
+                wordlist: None,
+            } => StrongholdProcedure::BIP39Generate(BIP39Generate {
+                passphrase: passphrase_from(passphrase),
+                output: output.into(),
+                language: MnemonicLanguage::English,
+            }),
+            ProcedureDto::GenerateKey {
+                return_public_key: true,
+                ..
+            } => return Err(Error::CompositeProcedure("GenerateKey")),
+            ProcedureDto::GenerateEd25519AndRegister { .. } => {
+                return Err(Error::CompositeProcedure("GenerateEd25519AndRegister"))
+            }
+            ProcedureDto::DeriveViewKeyFromSeed { .. } => {
+                return Err(Error::CompositeProcedure("DeriveViewKeyFromSeed"))
+            }
+            #[cfg(feature = "unsafe-export")]
+            ProcedureDto::ConvertMnemonicToSeed { .. } => {
+                return Err(Error::CompositeProcedure("ConvertMnemonicToSeed"))
+            }
+            ProcedureDto::ValidateMnemonic { .. } => {
+                return Err(Error::CompositeProcedure("ValidateMnemonic"))
+            }
+            ProcedureDto::Random { .. } => return Err(Error::CompositeProcedure("Random")),
+            ProcedureDto::ShamirSplit { .. } => {
+                return Err(Error::CompositeProcedure("ShamirSplit"))
+            }
+            ProcedureDto::ShamirCombine { .. } => {
+                return Err(Error::CompositeProcedure("ShamirCombine"))
+            }
+            ProcedureDto::ImportAleoPrivateKey { .. } => {
+                return Err(Error::CompositeProcedure("ImportAleoPrivateKey"))
+            }
+            ProcedureDto::GenerateKey {
+                ty,
+                output,
+                return_public_key: false,
+            } => StrongholdProcedure::GenerateKey(GenerateKey {
+                ty: ty.into(),
+                output: output.into(),
+            }),
+            ProcedureDto::PublicKey { ty, private_key } => {
+                StrongholdProcedure::PublicKey(PublicKey {
+                    ty: ty.into(),
                     private_key: private_key.into(),
-                    msg: msg.as_bytes().to_vec(),
+                })
+            }
+            ProcedureDto::GetPublicKeyString { .. } => {
+                return Err(Error::CompositeProcedure("GetPublicKeyString"))
+            }
+            ProcedureDto::GetPublicIdentity { .. } => {
+                return Err(Error::CompositeProcedure("GetPublicIdentity"))
+            }
+            ProcedureDto::Ed25519Sign { private_key, msg } => {
+                StrongholdProcedure::Ed25519Sign(Ed25519Sign {
+                    private_key: private_key.into(),
+                    msg: msg.into_bytes(),
                 })
             }
             ProcedureDto::AleoSign {
@@ -302,9 +1336,19 @@ impl<N: Network> From<ProcedureDto<N>> for StrongholdProcedure<N> {
                 ext,
             } => StrongholdProcedure::AleoSign(AleoSign {
                 private_key: private_key.into(),
-                msg: msg.as_bytes().to_vec(),
+                msg: msg.into_bytes(),
                 ext,
             }),
+            ProcedureDto::Ed25519SignStructured { .. } => {
+                return Err(Error::CompositeProcedure("Ed25519SignStructured"))
+            }
+            ProcedureDto::VerifyEd25519 { .. } => {
+                return Err(Error::CompositeProcedure("VerifyEd25519"))
+            }
+            ProcedureDto::AleoSignStructured { .. } => {
+                return Err(Error::CompositeProcedure("AleoSignStructured"))
+            }
+            ProcedureDto::SignMany { .. } => return Err(Error::CompositeProcedure("SignMany")),
             ProcedureDto::GetAleoAddress { private_key, ext } => {
                 StrongholdProcedure::GetAleoAddress(GetAleoAddress {
                     private_key: private_key.into(),
@@ -318,6 +1362,9 @@ impl<N: Network> From<ProcedureDto<N>> for StrongholdProcedure<N> {
                 private_key: private_key.into(),
                 _network,
             }),
+            ProcedureDto::AleoScanRecords { .. } => {
+                return Err(Error::CompositeProcedure("AleoScanRecords"))
+            }
             ProcedureDto::AleoSignRequest {
                 program_id,
                 function_name,
@@ -326,31 +1373,45 @@ impl<N: Network> From<ProcedureDto<N>> for StrongholdProcedure<N> {
                 root_tvk,
                 is_root,
                 private_key,
-            } => StrongholdProcedure::AleoSignRequest(AleoSignRequest {
-                program_id,
-                function_name,
-                inputs,
-                input_types,
-                root_tvk,
-                is_root,
-                private_key: private_key.into(),
-            }),
+            } => {
+                inputs::validate_inputs(&inputs, &input_types)?;
+                StrongholdProcedure::AleoSignRequest(AleoSignRequest {
+                    program_id,
+                    function_name,
+                    inputs,
+                    input_types,
+                    root_tvk,
+                    is_root,
+                    private_key: private_key.into(),
+                })
+            }
+            ProcedureDto::AleoSignRequests { .. } => {
+                return Err(Error::CompositeProcedure("AleoSignRequests"))
+            }
             ProcedureDto::AleoAuthorize {
                 private_key,
                 program_id,
                 function_name,
                 inputs,
-            } => StrongholdProcedure::AleoAuthorize(AleoAuthorize {
-                private_key: private_key.into(),
-                program_id,
-                function_name,
-                inputs,
-            }),
+                input_types,
+                format: _,
+            } => {
+                if let Some(input_types) = input_types {
+                    inputs::validate_inputs(&inputs, &input_types)?;
+                }
+                StrongholdProcedure::AleoAuthorize(AleoAuthorize {
+                    private_key: private_key.into(),
+                    program_id,
+                    function_name,
+                    inputs,
+                })
+            }
             ProcedureDto::AleoAuthorizeFeePublic {
                 private_key,
                 base_fee_in_microcredits,
                 priority_fee_in_microcredits,
                 deployment_or_execution_id,
+                format: _,
             } => StrongholdProcedure::AleoAuthorizeFeePublic(AleoAuthorizeFeePublic {
                 private_key: private_key.into(),
                 base_fee_in_microcredits,
@@ -363,6 +1424,7 @@ impl<N: Network> From<ProcedureDto<N>> for StrongholdProcedure<N> {
                 base_fee_in_microcredits,
                 priority_fee_in_microcredits,
                 deployment_or_execution_id,
+                format: _,
             } => StrongholdProcedure::AleoAuthorizeFeePrivate(AleoAuthorizeFeePrivate {
                 private_key: private_key.into(),
                 credits,
@@ -370,207 +1432,2925 @@ impl<N: Network> From<ProcedureDto<N>> for StrongholdProcedure<N> {
                 priority_fee_in_microcredits,
                 deployment_or_execution_id,
             }),
+            ProcedureDto::AleoCombineAuthorizations { .. } => {
+                return Err(Error::CompositeProcedure("AleoCombineAuthorizations"))
+            }
             ProcedureDto::AleoExecute {
                 private_key,
                 program_id,
                 function_name,
                 inputs,
-                fee_record,
-                priority_fee_in_microcredits,
-                base_url,
-            } => StrongholdProcedure::AleoExecute(AleoExecute {
-                private_key: private_key.into(),
-                program_id,
-                function_name,
-                inputs,
-                fee_record,
+                fee,
                 priority_fee_in_microcredits,
+                base_fee_in_microcredits,
                 base_url,
-            }),
-        }
+                input_types,
+                idempotency_key: _,
+            } => {
+                if let Some(input_types) = input_types {
+                    inputs::validate_inputs(&inputs, &input_types)?;
+                }
+                let fee_record = match fee {
+                    FeeSource::Public => None,
+                    FeeSource::Private(record) => {
+                        let required = base_fee_in_microcredits
+                            .unwrap_or(0)
+                            .saturating_add(priority_fee_in_microcredits);
+                        let available = record_microcredits(&record)?;
+                        if available < required {
+                            return Err(Error::InsufficientFee {
+                                required,
+                                available,
+                            });
+                        }
+                        Some(record)
+                    }
+                };
+                StrongholdProcedure::AleoExecute(AleoExecute {
+                    private_key: private_key.into(),
+                    program_id,
+                    function_name,
+                    inputs,
+                    fee_record,
+                    priority_fee_in_microcredits,
+                    base_url,
+                })
+            }
+        };
+        Ok(procedure)
+    }
+}
+
+/// Hands `passphrase` over to the runtime's type, which wipes it in turn.
+fn passphrase_from(passphrase: Option<SecretString>) -> Passphrase {
+    Passphrase::from(passphrase.map(SecretString::into_inner).unwrap_or_default())
+}
+
+/// The `microcredits` held by a `credits.aleo` record.
+fn record_microcredits<N: Network>(record: &Record<N, Plaintext<N>>) -> Result<u64> {
+    let invalid = || Error::invalid_input("fee", "the record holds no `microcredits`");
+    let microcredits = Identifier::from_str("microcredits").map_err(|_| invalid())?;
+    match record.data().get(&microcredits) {
+        Some(
+            RecordEntry::Constant(Plaintext::Literal(Literal::U64(amount), _))
+            | RecordEntry::Public(Plaintext::Literal(Literal::U64(amount), _))
+            | RecordEntry::Private(Plaintext::Literal(Literal::U64(amount), _)),
+        ) => Ok(**amount),
+        _ => Err(invalid()),
     }
 }
 
 pub async fn initialize(
     collection: &StrongholdCollection,
     hash_function: PasswordHashFunction,
-    snapshot_path: PathBuf,
-    mut password: String,
+    snapshot_path: SnapshotPath,
+    password: impl Into<Password>,
+) -> Result<()> {
+    let password: Password = password.into();
+    collection.attempts.check(&snapshot_path)?;
+    let hash = (hash_function.0)(&password);
+    drop(password);
+    let stronghold = open_stronghold(collection, &snapshot_path, hash)?;
+
+    collection
+        .snapshots
+        .lock()
+        .unwrap()
+        .insert(snapshot_path.clone(), Arc::new(Mutex::new(stronghold)));
+
+    run_unlock_hook(collection, snapshot_path).await
+}
+
+/// Like [`initialize`], with the key obtained from a hardware token rather than hashed from
+/// a password.
+#[cfg(feature = "hardware-key")]
+pub async fn initialize_with_hardware_key(
+    collection: &StrongholdCollection,
+    key_source: &HardwareKeyFn,
+    snapshot_path: SnapshotPath,
+) -> Result<()> {
+    collection.attempts.check(&snapshot_path)?;
+    let mut key = key_source().await?;
+    let stronghold = open_stronghold(collection, &snapshot_path, std::mem::take(&mut *key))?;
+
+    collection
+        .snapshots
+        .lock()
+        .unwrap()
+        .insert(snapshot_path.clone(), Arc::new(Mutex::new(stronghold)));
+
+    run_unlock_hook(collection, snapshot_path).await
+}
+
+/// Like [`initialize`], with the key derived from several passwords, each hashed with the hash
+/// function at the same position, see [`combine`].
+pub async fn initialize_combined(
+    collection: &StrongholdCollection,
+    hash_functions: Vec<PasswordHashFunction>,
+    snapshot_path: SnapshotPath,
+    passwords: Vec<impl Into<Password>>,
+) -> Result<()> {
+    let passwords: Vec<Password> = passwords.into_iter().map(Into::into).collect();
+    if passwords.is_empty() || passwords.len() != hash_functions.len() {
+        return Err(Error::invalid_input(
+            "passwords",
+            format!(
+                "expected one password per hash function ({}), got {}",
+                hash_functions.len(),
+                passwords.len()
+            ),
+        ));
+    }
+    collection.attempts.check(&snapshot_path)?;
+    let hashes: Vec<_> = hash_functions
+        .iter()
+        .zip(&passwords)
+        .map(|(hash_function, password)| Zeroizing::new((hash_function.0)(password)))
+        .collect();
+    drop(passwords);
+    let mut key = combine::combine_hashes(&hashes);
+    drop(hashes);
+    let stronghold = open_stronghold(collection, &snapshot_path, std::mem::take(&mut *key))?;
+
+    collection
+        .snapshots
+        .lock()
+        .unwrap()
+        .insert(snapshot_path.clone(), Arc::new(Mutex::new(stronghold)));
+
+    run_unlock_hook(collection, snapshot_path).await
+}
+
+/// Runs the [`Builder::on_unlock`] hook, if any, for the snapshot just loaded.
+async fn run_unlock_hook(
+    collection: &StrongholdCollection,
+    snapshot_path: SnapshotPath,
+) -> Result<()> {
+    match &collection.config.unlock_hook {
+        Some(hook) => hook(collection, snapshot_path).await,
+        None => Ok(()),
+    }
+}
+
+/// Whether the snapshot at `snapshot_path` is loaded in the collection.
+pub async fn is_initialized(
+    collection: &StrongholdCollection,
+    snapshot_path: SnapshotPath,
+) -> bool {
+    collection
+        .snapshots
+        .lock()
+        .unwrap()
+        .contains_key(&snapshot_path)
+}
+
+/// Paths of the snapshots loaded in the collection, sorted.
+pub async fn collection_paths(collection: &StrongholdCollection) -> Vec<PathBuf> {
+    let mut paths: Vec<_> = collection
+        .snapshots
+        .lock()
+        .unwrap()
+        .keys()
+        .map(|path| path.as_path().to_path_buf())
+        .collect();
+    paths.sort();
+    paths
+}
+
+/// Like [`initialize`], but fails with [`Error::AlreadyInitialized`] instead of replacing
+/// a snapshot that is already loaded, so its unsaved changes can't be discarded by accident.
+pub async fn try_initialize(
+    collection: &StrongholdCollection,
+    hash_function: PasswordHashFunction,
+    snapshot_path: SnapshotPath,
+    password: impl Into<Password>,
 ) -> Result<()> {
+    let password: Password = password.into();
+    if collection
+        .snapshots
+        .lock()
+        .unwrap()
+        .contains_key(&snapshot_path)
+    {
+        return Err(Error::AlreadyInitialized);
+    }
+    collection.attempts.check(&snapshot_path)?;
+
     let hash = (hash_function.0)(&password);
-    password.zeroize();
-    let stronghold = Stronghold::new(snapshot_path.clone(), hash)?;
+    drop(password);
+    let stronghold = open_stronghold(collection, &snapshot_path, hash)?;
+
+    // Another caller may have initialized the same path while we were hashing.
+    match collection
+        .snapshots
+        .lock()
+        .unwrap()
+        .entry(snapshot_path.clone())
+    {
+        Entry::Occupied(_) => return Err(Error::AlreadyInitialized),
+        Entry::Vacant(entry) => {
+            entry.insert(Arc::new(Mutex::new(stronghold)));
+        }
+    }
+    run_unlock_hook(collection, snapshot_path).await
+}
+
+/// Like [`initialize`], but rejects a mismatched `confirmation` and stores a password verifier
+/// for [`verify_password`].
+pub async fn initialize_with_confirmation(
+    collection: &StrongholdCollection,
+    hash_function: PasswordHashFunction,
+    snapshot_path: SnapshotPath,
+    password: impl Into<Password>,
+    confirmation: impl Into<Password>,
+) -> Result<()> {
+    let password: Password = password.into();
+    let confirmation: Password = confirmation.into();
+    if password.as_str() != confirmation.as_str() {
+        return Err(Error::PasswordMismatch);
+    }
+    drop(confirmation);
+    collection.attempts.check(&snapshot_path)?;
+
+    let hash = Zeroizing::new((hash_function.0)(&password));
+    drop(password);
+    let stronghold = open_stronghold(collection, &snapshot_path, hash.to_vec())?;
+    if stronghold.verify_key(&hash)?.is_none() {
+        stronghold.set_password_verifier(&hash)?;
+    }
 
     collection
-        .0
+        .snapshots
+        .lock()
+        .unwrap()
+        .insert(snapshot_path.clone(), Arc::new(Mutex::new(stronghold)));
+
+    run_unlock_hook(collection, snapshot_path).await
+}
+
+/// Checks `password` against the verifier stored by [`initialize_with_confirmation`], failing
+/// with [`Error::PasswordVerifierMissing`] if there is none.
+pub async fn verify_password(
+    collection: &StrongholdCollection,
+    hash_function: PasswordHashFunction,
+    snapshot_path: SnapshotPath,
+    password: impl Into<Password>,
+) -> Result<bool> {
+    let password: Password = password.into();
+    collection.attempts.check(&snapshot_path)?;
+    let hash = Zeroizing::new((hash_function.0)(&password));
+    drop(password);
+    let verified = find_stronghold(collection, &snapshot_path)
+        .ok_or(Error::StrongholdNotInitialized)?
         .lock()
         .unwrap()
-        .insert(snapshot_path, stronghold);
+        .verify_key(&hash)?
+        .ok_or(Error::PasswordVerifierMissing)?;
+    collection.attempts.record(&snapshot_path, verified);
+    Ok(verified)
+}
 
+/// Registers the hash function a snapshot was created with, for [`reencrypt_with_new_kdf`].
+pub async fn reinitialize_hash_function(
+    collection: &StrongholdCollection,
+    snapshot_path: SnapshotPath,
+    hash_function: PasswordHashFunction,
+) -> Result<()> {
+    collection
+        .legacy_hash_functions
+        .lock()
+        .unwrap()
+        .insert(snapshot_path, Arc::from(hash_function.0));
     Ok(())
 }
 
-pub async fn destroy(collection: &StrongholdCollection, snapshot_path: PathBuf) -> Result<()> {
-    let mut collection = collection.0.lock().unwrap();
-    if let Some(stronghold) = collection.remove(&snapshot_path) {
-        if let Err(e) = stronghold.save() {
-            collection.insert(snapshot_path, stronghold);
+/// Opens a snapshot with the hash function registered by [`reinitialize_hash_function`] and
+/// re-encrypts it under `password` hashed with `hash_function`, saving it right away.
+pub async fn reencrypt_with_new_kdf(
+    collection: &StrongholdCollection,
+    hash_function: PasswordHashFunction,
+    snapshot_path: SnapshotPath,
+    password: impl Into<Password>,
+) -> Result<()> {
+    let password: Password = password.into();
+    if collection
+        .snapshots
+        .lock()
+        .unwrap()
+        .contains_key(&snapshot_path)
+    {
+        return Err(Error::AlreadyInitialized);
+    }
+    collection.attempts.check(&snapshot_path)?;
+
+    // Not held while hashing and decrypting, which may take a while.
+    let legacy = collection
+        .legacy_hash_functions
+        .lock()
+        .unwrap()
+        .get(&snapshot_path)
+        .cloned()
+        .ok_or(Error::LegacyHashFunctionMissing)?;
+    let old_hash = Zeroizing::new((*legacy)(&password));
+    let new_hash = Zeroizing::new((hash_function.0)(&password));
+    drop(password);
+
+    // Decrypting with the old hash is what proves the password is right.
+    let mut stronghold = open_stronghold(collection, &snapshot_path, old_hash.to_vec())?;
+    let had_verifier = stronghold.verify_key(&old_hash)?.is_some();
+    if had_verifier {
+        stronghold.set_password_verifier(&new_hash)?;
+    }
+    stronghold.rekey(new_hash.to_vec())?;
+    let mut legacy_hash_functions = collection.legacy_hash_functions.lock().unwrap();
+    // Unless another function was registered meanwhile.
+    if let Entry::Occupied(entry) = legacy_hash_functions.entry(snapshot_path.clone()) {
+        if Arc::ptr_eq(entry.get(), &legacy) {
+            entry.remove();
+        }
+    }
+    drop(legacy_hash_functions);
+
+    collection
+        .snapshots
+        .lock()
+        .unwrap()
+        .insert(snapshot_path.clone(), Arc::new(Mutex::new(stronghold)));
+    run_unlock_hook(collection, snapshot_path).await
+}
+
+/// Saves and unloads the snapshot. It stays loaded if saving fails.
+pub async fn destroy(collection: &StrongholdCollection, snapshot_path: SnapshotPath) -> Result<()> {
+    let removed = collection.snapshots.lock().unwrap().remove(&snapshot_path);
+    if let Some(stronghold) = removed {
+        let result = stronghold.lock().unwrap().save();
+        if let Err(e) = result {
+            restore_stronghold(collection, snapshot_path, stronghold);
             return Err(e);
         }
     }
     Ok(())
 }
 
-pub async fn save(collection: &StrongholdCollection, snapshot_path: PathBuf) -> Result<()> {
-    let collection = collection.0.lock().unwrap();
-    if let Some(stronghold) = collection.get(&snapshot_path) {
-        stronghold.save()?;
+/// Saves and unloads every snapshot. The ones that fail to save stay loaded and are reported
+/// together in [`Error::Shutdown`].
+pub async fn shutdown(collection: &StrongholdCollection) -> Result<()> {
+    let snapshots = std::mem::take(&mut *collection.snapshots.lock().unwrap());
+    let mut failures = Vec::new();
+    for (path, stronghold) in snapshots {
+        let result = {
+            let stronghold = stronghold.lock().unwrap();
+            stronghold.save().and_then(|()| stronghold.clear())
+        };
+        if let Err(e) = result {
+            failures.push((path.as_path().to_path_buf(), e));
+            restore_stronghold(collection, path, stronghold);
+        }
+    }
+    collection.legacy_hash_functions.lock().unwrap().clear();
+    if let Some(debouncer) = &collection.debouncer {
+        debouncer.clear();
+    }
+
+    if failures.is_empty() {
+        Ok(())
+    } else {
+        Err(Error::Shutdown(failures))
     }
-    Ok(())
 }
 
-pub async fn create_client(
+/// Saves the snapshot if it was written since it was last saved, see [`Stronghold::save`].
+pub async fn save(collection: &StrongholdCollection, snapshot_path: SnapshotPath) -> Result<()> {
+    save_with(collection, &snapshot_path, Stronghold::save)
+}
+
+/// [`save`], rewriting the snapshot file even if nothing changed.
+pub async fn force_save(
     collection: &StrongholdCollection,
-    snapshot_path: PathBuf,
-    client: BytesDto,
+    snapshot_path: SnapshotPath,
 ) -> Result<()> {
-    let stronghold = get_stronghold(collection, snapshot_path)?;
-    stronghold.create_client(client)?;
-    Ok(())
+    save_with(collection, &snapshot_path, Stronghold::force_save)
 }
 
-pub async fn load_client(
+fn save_with(
     collection: &StrongholdCollection,
-    snapshot_path: PathBuf,
-    client: BytesDto,
+    snapshot_path: &SnapshotPath,
+    save: fn(&Stronghold) -> Result<()>,
 ) -> Result<()> {
-    let stronghold = get_stronghold(collection, snapshot_path)?;
-    stronghold.load_client(client)?;
+    if let Some(stronghold) = find_stronghold(collection, snapshot_path) {
+        // Before saving, so that a write landing meanwhile schedules a save of its own.
+        if let Some(debouncer) = &collection.debouncer {
+            debouncer.mark_clean(snapshot_path);
+        }
+        if let Err(e) = save(&stronghold.lock().unwrap()) {
+            if let Some(debouncer) = &collection.debouncer {
+                debouncer.mark_dirty(snapshot_path);
+            }
+            return Err(e);
+        }
+    }
     Ok(())
 }
 
-pub async fn get_store_record(
+/// When the snapshot file was last modified, whether or not the snapshot is initialized.
+/// Fails with [`Error::SnapshotNotFound`] if there is no file.
+pub async fn snapshot_last_modified(
     collection: &StrongholdCollection,
-    snapshot_path: PathBuf,
-    client: BytesDto,
-    key: String,
-) -> Result<Option<Vec<u8>>> {
-    let client = get_client(collection, snapshot_path, client)?;
-    client.store().get(key.as_ref()).map_err(Into::into)
+    snapshot_path: SnapshotPath,
+) -> Result<SystemTime> {
+    if collection.config.snapshot_store.is_some() {
+        return Err(Error::Unsupported(
+            "modification times of snapshots in a snapshot store",
+        ));
+    }
+    match std::fs::metadata(snapshot_path.as_path()) {
+        Ok(metadata) => Ok(metadata.modified()?),
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => Err(Error::SnapshotNotFound),
+        Err(e) => Err(e.into()),
+    }
 }
 
-pub async fn save_store_record(
+/// When the snapshot was first written after it was last saved, or `None` if all its writes
+/// are saved, see [`Stronghold::dirty_since`].
+pub async fn snapshot_dirty_since(
     collection: &StrongholdCollection,
-    snapshot_path: PathBuf,
-    client: BytesDto,
-    key: String,
-    value: Vec<u8>,
-    lifetime: Option<Duration>,
-) -> Result<Option<Vec<u8>>> {
-    let client = get_client(collection, snapshot_path, client)?;
-    client
-        .store()
-        .insert(key.as_bytes().to_vec(), value, lifetime)
-        .map_err(Into::into)
+    snapshot_path: SnapshotPath,
+) -> Result<Option<SystemTime>> {
+    with_stronghold(collection, &snapshot_path, |stronghold| {
+        Ok(stronghold.dirty_since())
+    })
 }
 
-pub async fn remove_store_record(
+/// Verifies that the snapshot can be decrypted and read end-to-end, without modifying it.
+pub async fn health_check(
     collection: &StrongholdCollection,
-    snapshot_path: PathBuf,
-    client: BytesDto,
-    key: String,
-) -> Result<Option<Vec<u8>>> {
-    let client = get_client(collection, snapshot_path, client)?;
-    client.store().delete(key.as_ref()).map_err(Into::into)
+    snapshot_path: SnapshotPath,
+) -> Result<HealthReport> {
+    Ok(find_stronghold(collection, &snapshot_path)
+        .ok_or(Error::StrongholdNotInitialized)?
+        .lock()
+        .unwrap()
+        .health_check())
 }
 
-pub async fn save_secret(
+/// Checks the snapshot file against the checksum of its last save, see
+/// [`Stronghold::verify_integrity`]. The snapshot must be initialized.
+pub async fn verify_snapshot_integrity(
     collection: &StrongholdCollection,
-    snapshot_path: PathBuf,
-    client: BytesDto,
-    vault: BytesDto,
-    record_path: BytesDto,
-    secret: Vec<u8>,
+    snapshot_path: SnapshotPath,
+) -> Result<bool> {
+    // Holding the lock keeps a save from changing the file halfway through.
+    with_stronghold(collection, &snapshot_path, Stronghold::verify_integrity)
+}
+
+pub async fn create_client(
+    collection: &StrongholdCollection,
+    snapshot_path: SnapshotPath,
+    client: ClientPath,
 ) -> Result<()> {
-    let client = get_client(collection, snapshot_path, client)?;
-    client
-        .vault(&vault)
-        .write_secret(
-            Location::generic(vault, record_path),
-            zeroize::Zeroizing::new(secret),
-        )
-        .map_err(Into::into)
+    with_stronghold(collection, &snapshot_path, |stronghold| {
+        stronghold.inner().create_client(&client)?;
+        // A handle cached before shares the replaced state.
+        stronghold.forget_client(&client);
+        Ok(())
+    })?;
+    mark_dirty(collection, &snapshot_path);
+    Ok(())
 }
 
-pub async fn unsafe_get_secret(
+pub async fn load_client(
     collection: &StrongholdCollection,
-    snapshot_path: PathBuf,
-    client: BytesDto,
-    vault: BytesDto,
-    record_path: BytesDto,
-) -> Result<Zeroizing<Vec<u8>>> {
-    let client = get_client(collection, snapshot_path, client)?;
-    client
-        .vault(&vault)
-        .read_secret(record_path)
-        .map_err(Into::into)
+    snapshot_path: SnapshotPath,
+    client: ClientPath,
+) -> Result<()> {
+    with_stronghold(collection, &snapshot_path, |stronghold| {
+        stronghold.inner().load_client(&client)?;
+        stronghold.forget_client(&client);
+        Ok(())
+    })
 }
 
-pub async fn remove_secret(
+/// A handle of `client`, or `None` if the snapshot isn't initialized.
+///
+/// Writes through it must be followed by [`mark_snapshot_dirty`], or [`save`] skips them.
+pub async fn try_get_client(
     collection: &StrongholdCollection,
-    snapshot_path: PathBuf,
-    client: BytesDto,
-    vault: BytesDto,
-    record_path: BytesDto,
+    snapshot_path: SnapshotPath,
+    client: ClientPath,
+) -> Result<Option<Client>> {
+    find_client(collection, &snapshot_path, client)
+}
+
+/// Records a write made through a client handle, so that the next [`save`] persists it and a
+/// debounced save is scheduled.
+pub async fn mark_snapshot_dirty(
+    collection: &StrongholdCollection,
+    snapshot_path: SnapshotPath,
 ) -> Result<()> {
-    let client = get_client(collection, snapshot_path, client)?;
-    client
-        .vault(vault)
-        .delete_secret(record_path)
-        .map(|_| ())
-        .map_err(Into::into)
+    find_stronghold(collection, &snapshot_path).ok_or(Error::StrongholdNotInitialized)?;
+    mark_dirty(collection, &snapshot_path);
+    Ok(())
 }
 
-pub async fn execute_procedure<N: Network>(
+/// Loads `client`, or creates it if the snapshot has none, returning whether it was created.
+pub async fn load_or_create_client(
     collection: &StrongholdCollection,
-    snapshot_path: PathBuf,
-    client: BytesDto,
-    procedure: ProcedureDto<N>,
-) -> Result<Vec<u8>> {
-    let client = get_client(collection, snapshot_path, client)?;
-    client
-        .execute_procedure(StrongholdProcedure::from(procedure))
-        .map(Into::into)
-        .map_err(Into::into)
+    snapshot_path: SnapshotPath,
+    client: ClientPath,
+) -> Result<bool> {
+    with_stronghold(collection, &snapshot_path, |stronghold| {
+        let inner = stronghold.inner();
+        if inner.get_client(&client).is_ok() {
+            return Ok(false);
+        }
+        let created = match inner.load_client(&client) {
+            Ok(_) => false,
+            Err(iota_stronghold::ClientError::ClientDataNotPresent) => {
+                inner.create_client(&client)?;
+                stronghold.mark_dirty();
+                true
+            }
+            Err(e) => return Err(e.into()),
+        };
+        stronghold.forget_client(&client);
+        Ok(created)
+    })
 }
 
-fn get_stronghold(
+/// Re-reads the state of `client` from the snapshot file, discarding unsaved changes.
+pub async fn reload_client(
     collection: &StrongholdCollection,
-    snapshot_path: PathBuf,
-) -> Result<iota_stronghold::Stronghold> {
-    let collection = collection.0.lock().unwrap();
-    if let Some(stronghold) = collection.get(&snapshot_path) {
-        Ok(stronghold.inner().clone())
-    } else {
-        Err(Error::StrongholdNotInitialized)
-    }
+    snapshot_path: SnapshotPath,
+    client: ClientPath,
+) -> Result<()> {
+    find_stronghold(collection, &snapshot_path)
+        .ok_or(Error::StrongholdNotInitialized)?
+        .lock()
+        .unwrap()
+        .reload_client(client)
 }
 
-fn get_client(
+/// Copies `clients` from the snapshot at `from` into the one at `to`, replacing clients of the
+/// same path there, then saves both. Both snapshots must be initialized.
+pub async fn migrate_between_snapshots(
     collection: &StrongholdCollection,
-    snapshot_path: PathBuf,
-    client: BytesDto,
-) -> Result<Client> {
-    let collection = collection.0.lock().unwrap();
-    if let Some(stronghold) = collection.get(&snapshot_path) {
-        stronghold.get_client(client).map_err(Into::into)
-    } else {
-        Err(Error::StrongholdNotInitialized)
+    from: SnapshotPath,
+    to: SnapshotPath,
+    clients: Vec<ClientPath>,
+) -> Result<()> {
+    if from == to {
+        return Err(Error::invalid_input("to", "must differ from `from`"));
+    }
+    for client in &clients {
+        validate_path("client", client, collection.config.max_path_length)?;
     }
+    let source = find_stronghold(collection, &from).ok_or(Error::StrongholdNotInitialized)?;
+    let target = find_stronghold(collection, &to).ok_or(Error::StrongholdNotInitialized)?;
+    // Always locked in the same order, so two opposite migrations can't deadlock.
+    let (source, target) = if from < to {
+        let source = source.lock().unwrap();
+        (source, target.lock().unwrap())
+    } else {
+        let target = target.lock().unwrap();
+        (source.lock().unwrap(), target)
+    };
+    let clients: Vec<Vec<u8>> = clients.iter().map(|c| c.as_ref().to_vec()).collect();
+    target.import_clients(&source, &clients)?;
+    source.save()?;
+    target.save()
 }
 
-pub enum PasswordHashFunctionKind {
+/// Increments the counter of `vault` in `client` and returns its new value, 1 the first time.
+pub async fn next_counter(
+    collection: &StrongholdCollection,
+    snapshot_path: SnapshotPath,
+    client: ClientPath,
+    vault: BytesDto,
+) -> Result<usize> {
+    validate_path("vault", &vault, collection.config.max_path_length)?;
+    let value = with_stronghold(collection, &snapshot_path, |stronghold| {
+        stronghold.client(&client)?;
+        stronghold.next_counter(client.as_ref(), vault.as_ref())
+    })?;
+    mark_dirty(collection, &snapshot_path);
+    Ok(value)
+}
+
+/// Reads the store record at `key`.
+///
+/// The `_namespaced` variants, such as [`get_store_record_namespaced`], take a [`StoreKey`]
+/// instead of its encoding.
+pub async fn get_store_record(
+    collection: &StrongholdCollection,
+    snapshot_path: SnapshotPath,
+    client: ClientPath,
+    key: String,
+) -> Result<Option<Vec<u8>>> {
+    let client = get_client(collection, snapshot_path, client)?;
+    client.store().get(encode_key(&key)).map_err(Into::into)
+}
+
+/// [`get_store_record`] at a [`StoreKey`].
+pub async fn get_store_record_namespaced(
+    collection: &StrongholdCollection,
+    snapshot_path: SnapshotPath,
+    client: ClientPath,
+    key: StoreKey,
+) -> Result<Option<Vec<u8>>> {
+    get_store_record(collection, snapshot_path, client, key.to_string()).await
+}
+
+/// [`get_store_record`] returning `default` if there is no record at `key`.
+pub async fn get_store_record_or(
+    collection: &StrongholdCollection,
+    snapshot_path: SnapshotPath,
+    client: ClientPath,
+    key: String,
+    default: Vec<u8>,
+) -> Result<Vec<u8>> {
+    get_store_record(collection, snapshot_path, client, key)
+        .await
+        .map(|value| value.unwrap_or(default))
+}
+
+/// Reads several store records with one client lookup, returning the values in the order of
+/// `keys` and `None` for the missing ones.
+pub async fn get_multiple_store_records(
+    collection: &StrongholdCollection,
+    snapshot_path: SnapshotPath,
+    client: ClientPath,
+    keys: Vec<String>,
+) -> Result<Vec<Option<Vec<u8>>>> {
+    let client = get_client(collection, snapshot_path, client)?;
+    let store = client.store();
+    keys.iter()
+        .map(|key| store.get(encode_key(key)).map_err(Into::into))
+        .collect()
+}
+
+pub async fn save_store_record(
+    collection: &StrongholdCollection,
+    snapshot_path: SnapshotPath,
+    client: ClientPath,
+    key: String,
+    value: Vec<u8>,
+    lifetime: Option<Duration>,
+) -> Result<Option<Vec<u8>>> {
+    reserve(collection, &snapshot_path, key.len() + value.len())?;
+    let client_handle = get_client(collection, snapshot_path.clone(), client.clone())?;
+    let previous = client_handle
+        .store()
+        .insert(encode_key(&key).to_vec(), value, lifetime)?;
+    with_stronghold(collection, &snapshot_path, |stronghold| {
+        stronghold.set_store_expiry(client.as_ref(), encode_key(&key), lifetime)
+    })?;
+    mark_dirty(collection, &snapshot_path);
+    Ok(previous)
+}
+
+/// [`save_store_record`] at a [`StoreKey`].
+pub async fn save_store_record_namespaced(
+    collection: &StrongholdCollection,
+    snapshot_path: SnapshotPath,
+    client: ClientPath,
+    key: StoreKey,
+    value: Vec<u8>,
+    lifetime: Option<Duration>,
+) -> Result<Option<Vec<u8>>> {
+    save_store_record(
+        collection,
+        snapshot_path,
+        client,
+        key.to_string(),
+        value,
+        lifetime,
+    )
+    .await
+}
+
+/// Returns the length and remaining lifetime of a store record, without its value.
+pub async fn peek_store_record(
+    collection: &StrongholdCollection,
+    snapshot_path: SnapshotPath,
+    client: ClientPath,
+    key: String,
+) -> Result<Option<StoreRecordMeta>> {
+    let client_handle = get_client(collection, snapshot_path.clone(), client.clone())?;
+    let Some(len) = client_handle
+        .store()
+        .get(encode_key(&key))?
+        .map(|value| Zeroizing::new(value).len())
+    else {
+        return Ok(None);
+    };
+    with_stronghold(collection, &snapshot_path, |stronghold| {
+        store_record_meta(stronghold, &client, &key, len)
+    })
+    .map(Some)
+}
+
+/// [`peek_store_record`] at a [`StoreKey`].
+pub async fn peek_store_record_namespaced(
+    collection: &StrongholdCollection,
+    snapshot_path: SnapshotPath,
+    client: ClientPath,
+    key: StoreKey,
+) -> Result<Option<StoreRecordMeta>> {
+    peek_store_record(collection, snapshot_path, client, key.to_string()).await
+}
+
+/// Lists the store records of `page` with their length and remaining lifetime, sorted by key.
+pub async fn list_store_records(
+    collection: &StrongholdCollection,
+    snapshot_path: SnapshotPath,
+    client: ClientPath,
+    page: PageRequest,
+) -> Result<Page<(String, StoreRecordMeta)>> {
+    list_store_records_under(collection, snapshot_path, client, None, page)
+}
+
+/// [`list_store_records`] for the records of `namespace`, returning their keys without
+/// the namespace, i.e. the id of their [`StoreKey`].
+pub async fn list_store_records_namespaced(
+    collection: &StrongholdCollection,
+    snapshot_path: SnapshotPath,
+    client: ClientPath,
+    namespace: String,
+    page: PageRequest,
+) -> Result<Page<(String, StoreRecordMeta)>> {
+    list_store_records_under(collection, snapshot_path, client, Some(&namespace), page)
+}
+
+fn list_store_records_under(
+    collection: &StrongholdCollection,
+    snapshot_path: SnapshotPath,
+    client: ClientPath,
+    namespace: Option<&str>,
+    page: PageRequest,
+) -> Result<Page<(String, StoreRecordMeta)>> {
+    let prefix = namespace
+        .map(StoreKey::namespace_prefix)
+        .unwrap_or_default();
+    let client_handle = get_client(collection, snapshot_path.clone(), client.clone())?;
+    let store = client_handle.store();
+    let mut keys = Vec::new();
+    for key in store.keys()? {
+        if key.starts_with(encode_key(&prefix)) {
+            keys.push(decode_key(key)?);
+        }
+    }
+    keys.sort();
+    let keys = paginate(keys, page);
+    let mut records = Vec::with_capacity(keys.items.len());
+    for key in keys.items {
+        if let Some(len) = store
+            .get(encode_key(&key))?
+            .map(|value| Zeroizing::new(value).len())
+        {
+            let meta = with_stronghold(collection, &snapshot_path, |stronghold| {
+                store_record_meta(stronghold, &client, &key, len)
+            })?;
+            records.push((key[prefix.len()..].to_string(), meta));
+        }
+    }
+    Ok(Page {
+        items: records,
+        total: keys.total,
+    })
+}
+
+/// Changes how long the store record `key` lives from now on, `None` meaning forever. Fails
+/// with [`Error::StoreRecordNotFound`] if there is no such record.
+pub async fn set_store_record_lifetime(
+    collection: &StrongholdCollection,
+    snapshot_path: SnapshotPath,
+    client: ClientPath,
+    key: String,
+    lifetime: Option<Duration>,
+) -> Result<()> {
+    let client_handle = get_client(collection, snapshot_path.clone(), client.clone())?;
+    let store = client_handle.store();
+    let value = store
+        .get(encode_key(&key))?
+        .ok_or(Error::StoreRecordNotFound)?;
+    // The store only takes a lifetime on insert.
+    store.insert(encode_key(&key).to_vec(), value, lifetime)?;
+    with_stronghold(collection, &snapshot_path, |stronghold| {
+        stronghold.set_store_expiry(client.as_ref(), encode_key(&key), lifetime)
+    })?;
+    mark_dirty(collection, &snapshot_path);
+    Ok(())
+}
+
+fn store_record_meta(
+    stronghold: &Stronghold,
+    client: &ClientPath,
+    key: &str,
+    len: usize,
+) -> Result<StoreRecordMeta> {
+    let expiry = stronghold.store_expiry(client.as_ref(), encode_key(key))?;
+    Ok(StoreRecordMeta {
+        len,
+        expires_in: expiry
+            .map(|expiry| expiry.duration_since(SystemTime::now()).unwrap_or_default()),
+    })
+}
+
+/// Encodes `value` with the collection's [`Codec`] and saves it like [`save_store_record`].
+pub async fn save_typed<T: Serialize>(
+    collection: &StrongholdCollection,
+    snapshot_path: SnapshotPath,
+    client: ClientPath,
+    key: String,
+    value: &T,
+    lifetime: Option<Duration>,
+) -> Result<()> {
+    let value = collection.config.codec.encode(value)?;
+    save_store_record(collection, snapshot_path, client, key, value, lifetime).await?;
+    Ok(())
+}
+
+/// Reads a store record written by [`save_typed`] and decodes it with the collection's [`Codec`].
+pub async fn get_typed<T: DeserializeOwned>(
+    collection: &StrongholdCollection,
+    snapshot_path: SnapshotPath,
+    client: ClientPath,
+    key: String,
+) -> Result<Option<T>> {
+    get_store_record(collection, snapshot_path, client, key)
+        .await?
+        .map(|value| collection.config.codec.decode(&value))
+        .transpose()
+}
+
+/// Encrypts `value` with the secret at `sealing_vault`/`sealing_record` and saves it like
+/// [`save_store_record`], see [`sealed`]. Read it back with [`get_store_record_sealed`].
+#[allow(clippy::too_many_arguments)]
+pub async fn save_store_record_sealed(
+    collection: &StrongholdCollection,
+    snapshot_path: SnapshotPath,
+    client: ClientPath,
+    key: String,
+    value: Vec<u8>,
+    lifetime: Option<Duration>,
+    sealing_vault: VaultPath,
+    sealing_record: RecordPath,
+) -> Result<()> {
+    let mut value = Zeroizing::new(value);
+    validate_vault_and_record(collection, &sealing_vault, &sealing_record)?;
+    let client_handle = get_client(collection, snapshot_path.clone(), client.clone())?;
+    let sealed = sealed::seal(
+        &client_handle,
+        Location::generic(sealing_vault, sealing_record),
+        encode_key(&key),
+        std::mem::take(&mut *value),
+    )?;
+    save_store_record(collection, snapshot_path, client, key, sealed, lifetime).await?;
+    Ok(())
+}
+
+/// Reads and decrypts a store record written by [`save_store_record_sealed`] with the same
+/// sealing key.
+pub async fn get_store_record_sealed(
+    collection: &StrongholdCollection,
+    snapshot_path: SnapshotPath,
+    client: ClientPath,
+    key: String,
+    sealing_vault: VaultPath,
+    sealing_record: RecordPath,
+) -> Result<Option<Vec<u8>>> {
+    validate_vault_and_record(collection, &sealing_vault, &sealing_record)?;
+    let client_handle = get_client(collection, snapshot_path, client)?;
+    let Some(sealed) = client_handle.store().get(encode_key(&key))? else {
+        return Ok(None);
+    };
+    sealed::unseal(
+        &client_handle,
+        Location::generic(sealing_vault, sealing_record),
+        encode_key(&key),
+        &sealed,
+    )
+    .map(Some)
+}
+
+pub async fn remove_store_record(
+    collection: &StrongholdCollection,
+    snapshot_path: SnapshotPath,
+    client: ClientPath,
+    key: String,
+) -> Result<Option<Vec<u8>>> {
+    let client_handle = get_client(collection, snapshot_path.clone(), client.clone())?;
+    let previous = client_handle.store().delete(encode_key(&key))?;
+    with_stronghold(collection, &snapshot_path, |stronghold| {
+        stronghold.set_store_expiry(client.as_ref(), encode_key(&key), None)
+    })?;
+    mark_dirty(collection, &snapshot_path);
+    Ok(previous)
+}
+
+/// [`remove_store_record`] at a [`StoreKey`].
+pub async fn remove_store_record_namespaced(
+    collection: &StrongholdCollection,
+    snapshot_path: SnapshotPath,
+    client: ClientPath,
+    key: StoreKey,
+) -> Result<Option<Vec<u8>>> {
+    remove_store_record(collection, snapshot_path, client, key.to_string()).await
+}
+
+/// Writes `new` at `key` if the current value is `expected`, `None` meaning no record, and
+/// fails with [`Error::Conflict`] otherwise. Runs under the lock of the snapshot.
+pub async fn compare_and_swap_store_record(
+    collection: &StrongholdCollection,
+    snapshot_path: SnapshotPath,
+    client: ClientPath,
+    key: String,
+    expected: Option<Vec<u8>>,
+    new: Vec<u8>,
+) -> Result<()> {
+    reserve(collection, &snapshot_path, key.len() + new.len())?;
+    with_stronghold(collection, &snapshot_path, |stronghold| {
+        let store = stronghold.client(&client)?.store();
+        if store.get(encode_key(&key))? != expected {
+            return Err(Error::Conflict);
+        }
+        store.insert(encode_key(&key).to_vec(), new, None)?;
+        stronghold.set_store_expiry(client.as_ref(), encode_key(&key), None)
+    })?;
+    mark_dirty(collection, &snapshot_path);
+    Ok(())
+}
+
+/// Keeps `value` at `key` until the snapshot is unloaded, returning the previous value. Session
+/// records are never saved.
+pub async fn save_session_record(
+    collection: &StrongholdCollection,
+    snapshot_path: SnapshotPath,
+    client: ClientPath,
+    key: String,
+    value: Vec<u8>,
+) -> Result<Option<Vec<u8>>> {
+    with_stronghold(collection, &snapshot_path, |stronghold| {
+        Ok(stronghold.insert_session_entry(client.as_ref(), encode_key(&key), value))
+    })
+}
+
+/// Reads a session entry written by [`save_session_record`].
+pub async fn get_session_record(
+    collection: &StrongholdCollection,
+    snapshot_path: SnapshotPath,
+    client: ClientPath,
+    key: String,
+) -> Result<Option<Vec<u8>>> {
+    with_stronghold(collection, &snapshot_path, |stronghold| {
+        Ok(stronghold.session_entry(client.as_ref(), encode_key(&key)))
+    })
+}
+
+pub async fn remove_session_record(
+    collection: &StrongholdCollection,
+    snapshot_path: SnapshotPath,
+    client: ClientPath,
+    key: String,
+) -> Result<Option<Vec<u8>>> {
+    with_stronghold(collection, &snapshot_path, |stronghold| {
+        Ok(stronghold.remove_session_entry(client.as_ref(), encode_key(&key)))
+    })
+}
+
+/// Returns every store entry whose key starts with `prefix`, sorted by key.
+pub async fn get_store_records_by_prefix(
+    collection: &StrongholdCollection,
+    snapshot_path: SnapshotPath,
+    client: ClientPath,
+    prefix: String,
+) -> Result<Vec<(String, Vec<u8>)>> {
+    get_store_records_under(collection, snapshot_path, client, None, prefix)
+}
+
+/// [`get_store_records_by_prefix`] for the entries of `namespace` whose id starts with
+/// `prefix`, returning their keys without the namespace.
+pub async fn get_store_records_by_prefix_namespaced(
+    collection: &StrongholdCollection,
+    snapshot_path: SnapshotPath,
+    client: ClientPath,
+    namespace: String,
+    prefix: String,
+) -> Result<Vec<(String, Vec<u8>)>> {
+    get_store_records_under(collection, snapshot_path, client, Some(&namespace), prefix)
+}
+
+fn get_store_records_under(
+    collection: &StrongholdCollection,
+    snapshot_path: SnapshotPath,
+    client: ClientPath,
+    namespace: Option<&str>,
+    prefix: String,
+) -> Result<Vec<(String, Vec<u8>)>> {
+    let namespace_prefix = namespace
+        .map(StoreKey::namespace_prefix)
+        .unwrap_or_default();
+    let prefix = scoped_key(namespace, prefix);
+    let client = get_client(collection, snapshot_path, client)?;
+    let store = client.store();
+    let mut records = Vec::new();
+    for key in store.keys()? {
+        if !key.starts_with(encode_key(&prefix)) {
+            continue;
+        }
+        // Entries can expire between listing the keys and reading them.
+        if let Some(value) = store.get(&key)? {
+            let key = decode_key(key)?;
+            records.push((key[namespace_prefix.len()..].to_string(), value));
+        }
+    }
+    records.sort_by(|(a, _), (b, _)| a.cmp(b));
+    Ok(records)
+}
+
+/// Lists the keys in `namespace` that were written through a [`StoreKey`], sorted by id.
+pub async fn list_store_keys_in_namespace(
+    collection: &StrongholdCollection,
+    snapshot_path: SnapshotPath,
+    client: ClientPath,
+    namespace: String,
+    page: PageRequest,
+) -> Result<Page<StoreKey>> {
+    let client = get_client(collection, snapshot_path, client)?;
+    let prefix = StoreKey::namespace_prefix(&namespace);
+    let mut keys = Vec::new();
+    for key in client.store().keys()? {
+        if !key.starts_with(encode_key(&prefix)) {
+            continue;
+        }
+        if let Ok(key) = StoreKey::parse(&decode_key(key)?) {
+            keys.push(key);
+        }
+    }
+    keys.sort();
+    Ok(paginate(keys, page))
+}
+
+/// Writes `secret` to the record at `vault`/`record_path`. Without `overwrite` an existing
+/// secret is kept and [`Error::RecordExists`] returned.
+pub async fn save_secret(
+    collection: &StrongholdCollection,
+    snapshot_path: SnapshotPath,
+    client: ClientPath,
+    vault: VaultPath,
+    record_path: RecordPath,
+    secret: Vec<u8>,
+    overwrite: bool,
+) -> Result<()> {
+    let secret = Zeroizing::new(secret);
+    let operation = audit_operation(collection, || AuditOperation::WriteSecret {
+        location: Location::generic(vault.clone(), record_path.clone()).into(),
+    });
+    let result = (|| {
+        validate_vault_and_record(collection, &vault, &record_path)?;
+        let client_handle = get_client(collection, snapshot_path.clone(), client.clone())?;
+        let location = Location::generic(vault.clone(), record_path.clone());
+        if !overwrite && client_handle.record_exists(&location)? {
+            return Err(Error::RecordExists);
+        }
+        reserve(collection, &snapshot_path, secret.len())?;
+        client_handle.vault(&vault).write_secret(location, secret)?;
+        with_stronghold(collection, &snapshot_path, |stronghold| {
+            stronghold.index_record(client.as_ref(), vault.as_ref(), record_path.as_ref(), true)
+        })?;
+        mark_dirty(collection, &snapshot_path);
+        Ok(())
+    })();
+    audited(collection, &snapshot_path, &client, operation, result)
+}
+
+/// Stores `size_bytes` random bytes at `vault`/`record_path`, e.g. as a symmetric key. Fails
+/// with [`Error::RecordExists`] if the record holds a secret.
+pub async fn generate_secret(
+    collection: &StrongholdCollection,
+    snapshot_path: SnapshotPath,
+    client: ClientPath,
+    vault: VaultPath,
+    record_path: RecordPath,
+    size_bytes: usize,
+) -> Result<()> {
+    if size_bytes == 0 {
+        return Err(Error::invalid_input("size_bytes", "must not be zero"));
+    }
+    let mut secret = Zeroizing::new(vec![0u8; size_bytes]);
+    crypto::utils::rand::fill(&mut secret)?;
+    save_secret(
+        collection,
+        snapshot_path,
+        client,
+        vault,
+        record_path,
+        std::mem::take(&mut *secret),
+        false,
+    )
+    .await
+}
+
+/// Returns whether a secret is stored at `vault`/`record_path`, without reading it.
+pub async fn secret_exists(
+    collection: &StrongholdCollection,
+    snapshot_path: SnapshotPath,
+    client: ClientPath,
+    vault: VaultPath,
+    record_path: RecordPath,
+) -> Result<bool> {
+    validate_vault_and_record(collection, &vault, &record_path)?;
+    let client = get_client(collection, snapshot_path, client)?;
+    client
+        .record_exists(&Location::generic(vault, record_path))
+        .map_err(Into::into)
+}
+
+pub async fn unsafe_get_secret(
+    collection: &StrongholdCollection,
+    snapshot_path: SnapshotPath,
+    client: ClientPath,
+    vault: VaultPath,
+    record_path: RecordPath,
+) -> Result<Zeroizing<Vec<u8>>> {
+    let operation = audit_operation(collection, || AuditOperation::ReadSecret {
+        location: Location::generic(vault.clone(), record_path.clone()).into(),
+    });
+    let result = (|| {
+        validate_vault_and_record(collection, &vault, &record_path)?;
+        let client = get_client(collection, snapshot_path.clone(), client.clone())?;
+        client
+            .vault(&vault)
+            .read_secret(record_path)
+            .map_err(Into::into)
+    })();
+    audited(collection, &snapshot_path, &client, operation, result)
+}
+
+pub async fn remove_secret(
+    collection: &StrongholdCollection,
+    snapshot_path: SnapshotPath,
+    client: ClientPath,
+    vault: VaultPath,
+    record_path: RecordPath,
+) -> Result<()> {
+    let operation = audit_operation(collection, || AuditOperation::DeleteSecret {
+        location: Location::generic(vault.clone(), record_path.clone()).into(),
+    });
+    let result = (|| {
+        validate_vault_and_record(collection, &vault, &record_path)?;
+        let client_handle = get_client(collection, snapshot_path.clone(), client.clone())?;
+        client_handle.vault(&vault).delete_secret(&record_path)?;
+        with_stronghold(collection, &snapshot_path, |stronghold| {
+            stronghold.index_record(client.as_ref(), vault.as_ref(), record_path.as_ref(), false)
+        })?;
+        mark_dirty(collection, &snapshot_path);
+        Ok(())
+    })();
+    audited(collection, &snapshot_path, &client, operation, result)
+}
+
+/// Lists the record paths of `vault` written through this plugin, sorted.
+pub async fn list_vault_records(
+    collection: &StrongholdCollection,
+    snapshot_path: SnapshotPath,
+    client: ClientPath,
+    vault: VaultPath,
+    page: PageRequest,
+) -> Result<Page<Vec<u8>>> {
+    validate_path("vault", &vault, collection.config.max_path_length)?;
+    let client_handle = get_client(collection, snapshot_path.clone(), client.clone())?;
+    let records = with_stronghold(collection, &snapshot_path, |stronghold| {
+        stronghold.indexed_records(client.as_ref(), vault.as_ref())
+    })?;
+    let mut present = Vec::with_capacity(records.len());
+    // Skip the records deleted without going through the plugin.
+    for record in records {
+        if client_handle.record_exists(&Location::generic(vault.clone(), record.clone()))? {
+            present.push(record);
+        }
+    }
+    Ok(paginate(present, page))
+}
+
+/// Lists the clients with records written through the plugin, see [`list_vault_records`],
+/// and the clients loaded since the snapshot was opened, sorted.
+pub async fn list_clients(
+    collection: &StrongholdCollection,
+    snapshot_path: SnapshotPath,
+    page: PageRequest,
+) -> Result<Page<Vec<u8>>> {
+    let clients = with_stronghold(collection, &snapshot_path, |stronghold| {
+        stronghold.indexed_clients()
+    })?;
+    Ok(paginate(clients.into_iter().collect(), page))
+}
+
+/// Lists the vaults of `client` with records written through the plugin, see
+/// [`list_vault_records`], sorted.
+pub async fn list_vaults(
+    collection: &StrongholdCollection,
+    snapshot_path: SnapshotPath,
+    client: ClientPath,
+    page: PageRequest,
+) -> Result<Page<Vec<u8>>> {
+    let vaults = with_stronghold(collection, &snapshot_path, |stronghold| {
+        stronghold.indexed_vaults(client.as_ref())
+    })?;
+    Ok(paginate(vaults.into_iter().collect(), page))
+}
+
+pub async fn execute_procedure<N: Network>(
+    collection: &StrongholdCollection,
+    snapshot_path: SnapshotPath,
+    client: ClientPath,
+    procedure: ProcedureDto<N>,
+) -> Result<Vec<u8>> {
+    let timeout = collection.config.procedure_timeout;
+    execute_procedure_with_timeout(collection, snapshot_path, client, procedure, timeout).await
+}
+
+/// [`execute_procedure`] giving up with [`Error::Timeout`] after `timeout`, or never with
+/// `None`. Its idempotency key stays in flight until the procedure finishes.
+///
+/// Procedures holding the lock of the snapshot ignore `timeout`.
+pub async fn execute_procedure_with_timeout<N: Network>(
+    collection: &StrongholdCollection,
+    snapshot_path: SnapshotPath,
+    client: ClientPath,
+    procedure: ProcedureDto<N>,
+    timeout: Option<Duration>,
+) -> Result<Vec<u8>> {
+    let operation = audit_operation(collection, || audit_procedure(&procedure));
+    let result = (|| {
+        procedure.validate(&collection.config)?;
+        let client_handle = get_client(collection, snapshot_path.clone(), client.clone())?;
+        execute_validated(
+            collection,
+            &snapshot_path,
+            &client,
+            &client_handle,
+            procedure,
+            timeout,
+        )
+    })();
+    audited(collection, &snapshot_path, &client, operation, result)
+}
+
+/// Runs a validated `procedure` with deduplication and `timeout`, then indexes the records
+/// it wrote.
+fn execute_validated<N: Network>(
+    collection: &StrongholdCollection,
+    snapshot_path: &SnapshotPath,
+    client: &ClientPath,
+    client_handle: &Client,
+    procedure: ProcedureDto<N>,
+    timeout: Option<Duration>,
+) -> Result<Vec<u8>> {
+    let changes = procedure.record_changes();
+    let key = procedure.idempotency_key();
+    let cache_key = procedure.execution_cache_key();
+    let output = if procedure.runs_locked() {
+        run_deduplicated(collection, snapshot_path, client, key, cache_key, || {
+            run_procedure_locked(collection, snapshot_path, client_handle, procedure)
+        })
+    } else {
+        let client_handle = client_handle.clone();
+        run_deduplicated_with_timeout(
+            collection,
+            snapshot_path,
+            client,
+            key,
+            cache_key,
+            timeout,
+            move || run_procedure(&client_handle, procedure),
+        )
+    }?;
+    index_records(collection, snapshot_path, client, changes)?;
+    Ok(output)
+}
+
+/// [`execute_procedure`] reporting the stages of an `AleoExecute` to `on_progress`.
+pub async fn execute_procedure_with_progress<N: Network>(
+    collection: &StrongholdCollection,
+    snapshot_path: SnapshotPath,
+    client: ClientPath,
+    procedure: ProcedureDto<N>,
+    on_progress: impl Fn(ExecutionStage),
+) -> Result<Vec<u8>> {
+    if !matches!(procedure, ProcedureDto::AleoExecute { .. }) {
+        return execute_procedure(collection, snapshot_path, client, procedure).await;
+    }
+    let operation = audit_operation(collection, || audit_procedure(&procedure));
+    let result = (|| {
+        on_progress(ExecutionStage::Preparing);
+        procedure.validate(&collection.config)?;
+        let client_handle = get_client(collection, snapshot_path.clone(), client.clone())?;
+        on_progress(ExecutionStage::Proving);
+        let output = execute_validated(
+            collection,
+            &snapshot_path,
+            &client,
+            &client_handle,
+            procedure,
+            collection.config.procedure_timeout,
+        )?;
+        on_progress(ExecutionStage::Done);
+        Ok(output)
+    })();
+    audited(collection, &snapshot_path, &client, operation, result)
+}
+
+/// [`execute_procedure`] with extra input `secrets` that are never saved.
+///
+/// The secrets are written to the [`EPHEMERAL_VAULT`] and deleted, along with anything else
+/// there, once `procedure` finished. Holds the lock of the snapshot, without a timeout.
+pub async fn execute_procedure_with_ephemeral_secrets<N: Network>(
+    collection: &StrongholdCollection,
+    snapshot_path: SnapshotPath,
+    client: ClientPath,
+    secrets: Vec<(RecordPath, Zeroizing<Vec<u8>>)>,
+    procedure: ProcedureDto<N>,
+) -> Result<Vec<u8>> {
+    let operation = audit_operation(collection, || audit_procedure(&procedure));
+    let result =
+        execute_with_ephemeral_secrets(collection, &snapshot_path, &client, secrets, procedure);
+    audited(collection, &snapshot_path, &client, operation, result)
+}
+
+fn execute_with_ephemeral_secrets<N: Network>(
+    collection: &StrongholdCollection,
+    snapshot_path: &SnapshotPath,
+    client: &ClientPath,
+    secrets: Vec<(RecordPath, Zeroizing<Vec<u8>>)>,
+    procedure: ProcedureDto<N>,
+) -> Result<Vec<u8>> {
+    procedure.validate(&collection.config)?;
+    for (record, _) in &secrets {
+        validate_path("record", record, collection.config.max_path_length)?;
+    }
+    let (ephemeral, changes): (Vec<_>, Vec<_>) = procedure
+        .record_changes()
+        .into_iter()
+        .partition(|(location, _)| {
+            matches!(location, LocationDto::Generic { vault, .. } if vault.as_ref() == EPHEMERAL_VAULT)
+        });
+    let mut purged: Vec<Vec<u8>> = secrets
+        .iter()
+        .map(|(record, _)| record.as_ref().to_vec())
+        .collect();
+    for (location, _) in ephemeral {
+        if let LocationDto::Generic { record, .. } = location {
+            purged.push(record.into());
+        }
+    }
+
+    let output = with_stronghold(collection, snapshot_path, |stronghold| {
+        let client_handle = stronghold.client(client.clone())?;
+        let _purge = EphemeralSecrets {
+            client: &client_handle,
+            records: purged,
+        };
+        for (record, secret) in secrets {
+            client_handle
+                .vault(EPHEMERAL_VAULT)
+                .write_secret(Location::generic(EPHEMERAL_VAULT, record), secret)?;
+        }
+        run_procedure(&client_handle, procedure)
+    })?;
+    index_records(collection, snapshot_path, client, changes)?;
+    Ok(output)
+}
+
+/// Deletes the records of [`EPHEMERAL_VAULT`] when dropped, also when the procedure failed.
+struct EphemeralSecrets<'a> {
+    client: &'a Client,
+    records: Vec<Vec<u8>>,
+}
+
+impl Drop for EphemeralSecrets<'_> {
+    fn drop(&mut self) {
+        let vault = self.client.vault(EPHEMERAL_VAULT);
+        for record in &self.records {
+            if let Err(e) = vault.delete_secret(record) {
+                log::error!("failed to purge an ephemeral secret: {e}");
+            }
+        }
+    }
+}
+
+/// Runs independent `procedures` in order against a single client lookup and returns their outputs.
+///
+/// Execution stops at the first failing procedure and the error carries its index, 0 if the
+/// client can't be found. Procedures that already ran are not rolled back.
+pub async fn execute_procedures<N: Network>(
+    collection: &StrongholdCollection,
+    snapshot_path: SnapshotPath,
+    client: ClientPath,
+    procedures: Vec<ProcedureDto<N>>,
+) -> Result<Vec<Vec<u8>>> {
+    for (index, procedure) in procedures.iter().enumerate() {
+        procedure
+            .validate(&collection.config)
+            .map_err(|e| Error::batch_procedure(index, e))?;
+    }
+    let client_handle = get_client(collection, snapshot_path.clone(), client.clone())
+        .map_err(|e| Error::batch_procedure(0, e))?;
+    let timeout = collection.config.procedure_timeout;
+    let mut outputs = Vec::with_capacity(procedures.len());
+    for (index, procedure) in procedures.into_iter().enumerate() {
+        let operation = audit_operation(collection, || audit_procedure(&procedure));
+        let output = execute_validated(
+            collection,
+            &snapshot_path,
+            &client,
+            &client_handle,
+            procedure,
+            timeout,
+        );
+        let output = audited(collection, &snapshot_path, &client, operation, output)
+            .map_err(|e| Error::batch_procedure(index, e))?;
+        outputs.push(output);
+    }
+    Ok(outputs)
+}
+
+/// Runs `request` unless its idempotency key or execution cache key says it already ran.
+fn run_deduplicated(
+    collection: &StrongholdCollection,
+    snapshot_path: &SnapshotPath,
+    client: &ClientPath,
+    idempotency_key: Option<String>,
+    cache_key: Option<Vec<u8>>,
+    request: impl FnOnce() -> Result<Vec<u8>>,
+) -> Result<Vec<u8>> {
+    collection
+        .idempotency
+        .run(snapshot_path, idempotency_key, || {
+            collection
+                .execution_cache
+                .run(snapshot_path, client, cache_key, request)
+        })
+}
+
+/// [`run_deduplicated`] with [`run_with_timeout`]. A timed out `request` keeps its idempotency
+/// key in flight until it finishes, so that a retry doesn't run it a second time.
+fn run_deduplicated_with_timeout(
+    collection: &StrongholdCollection,
+    snapshot_path: &SnapshotPath,
+    client: &ClientPath,
+    idempotency_key: Option<String>,
+    cache_key: Option<Vec<u8>>,
+    timeout: Option<Duration>,
+    request: impl FnOnce() -> Result<Vec<u8>> + Send + 'static,
+) -> Result<Vec<u8>> {
+    let in_flight = match idempotency_key {
+        Some(key) => match collection.idempotency.begin(snapshot_path, key)? {
+            Begin::Done(output) => return Ok(output),
+            Begin::Started(in_flight) => Some(in_flight),
+        },
+        None => None,
+    };
+    collection
+        .execution_cache
+        .run(snapshot_path, client, cache_key, || {
+            run_with_timeout(timeout, move || {
+                let result = request();
+                if let Some(in_flight) = in_flight {
+                    in_flight.finish(&result);
+                }
+                result
+            })
+        })
+}
+
+/// Runs `f` on its own thread and stops waiting for it after `timeout`, or runs it right away
+/// without one.
+fn run_with_timeout<T: Send + 'static>(
+    timeout: Option<Duration>,
+    f: impl FnOnce() -> Result<T> + Send + 'static,
+) -> Result<T> {
+    let Some(timeout) = timeout else {
+        return f();
+    };
+    let (sender, receiver) = mpsc::channel();
+    let handle = thread::Builder::new()
+        .name("stronghold-procedure".into())
+        .spawn(move || {
+            // The receiver is gone if the caller timed out.
+            let _ = sender.send(f());
+        })?;
+    match receiver.recv_timeout(timeout) {
+        Ok(result) => result,
+        Err(RecvTimeoutError::Timeout) => Err(Error::Timeout(timeout)),
+        // The thread panicked before sending.
+        Err(RecvTimeoutError::Disconnected) => match handle.join() {
+            Err(panic) => std::panic::resume_unwind(panic),
+            Ok(()) => unreachable!("the procedure thread always sends its result"),
+        },
+    }
+}
+
+/// [`run_procedure`], under the lock of the snapshot if [`ProcedureDto::runs_locked`].
+fn run_procedure_locked<N: Network>(
+    collection: &StrongholdCollection,
+    snapshot_path: &SnapshotPath,
+    client: &Client,
+    procedure: ProcedureDto<N>,
+) -> Result<Vec<u8>> {
+    if !procedure.runs_locked() {
+        return run_procedure(client, procedure);
+    }
+    with_stronghold(collection, snapshot_path, |_| {
+        run_procedure(client, procedure)
+    })
+}
+
+/// Runs `procedure`, naming it in the errors of the runtime, see [`Error::ProcedureFailed`].
+fn run_procedure<N: Network>(client: &Client, procedure: ProcedureDto<N>) -> Result<Vec<u8>> {
+    let name = procedure.name();
+    run_procedure_steps(client, procedure).map_err(|e| e.in_procedure(name))
+}
+
+fn run_procedure_steps<N: Network>(client: &Client, procedure: ProcedureDto<N>) -> Result<Vec<u8>> {
+    match procedure {
+        ProcedureDto::BIP39Generate {
+            passphrase,
+            output,
+            wordlist: Some(words),
+        } => {
+            let mnemonic = wordlist::generate(&words)?;
+            let phrase = AsRef::<str>::as_ref(&*mnemonic).as_bytes().to_vec();
+            execute(
+                client,
+                StrongholdProcedure::<N>::BIP39Recover(BIP39Recover {
+                    mnemonic,
+                    passphrase: passphrase_from(passphrase),
+                    output: output.into(),
+                }),
+            )?;
+            Ok(phrase)
+        }
+        ProcedureDto::RotateBIP39Passphrase {
+            mnemonic,
+            seed,
+            old_passphrase,
+            new_passphrase,
+            output,
+        } => {
+            let LocationDto::Generic { vault, record } = &seed else {
+                return Err(Error::invalid_input(
+                    "seed",
+                    "must be at a generic location",
+                ));
+            };
+            if Location::from(seed.clone()) == Location::from(output.clone()) {
+                return Err(Error::invalid_input("output", "must differ from `seed`"));
+            }
+            let phrase = Zeroizing::new(mnemonic.into_inner());
+            let mut check = vec![0u8; 16];
+            crypto::utils::rand::fill(&mut check)?;
+            let matches = execute(
+                client,
+                StrongholdProcedure::<N>::BIP39Recover(BIP39Recover {
+                    mnemonic: Mnemonic::from(String::clone(&phrase)),
+                    passphrase: passphrase_from(old_passphrase),
+                    output: Location::generic(EPHEMERAL_VAULT, check.clone()),
+                }),
+            )
+            .and_then(|_| {
+                let expected =
+                    Zeroizing::new(client.vault(vault.clone()).read_secret(record.clone())?);
+                let actual =
+                    Zeroizing::new(client.vault(EPHEMERAL_VAULT).read_secret(check.clone())?);
+                // Compare without short-circuiting on the first differing byte.
+                let diff = actual
+                    .iter()
+                    .zip(expected.iter())
+                    .fold(expected.len() ^ actual.len(), |acc, (a, b)| {
+                        acc | usize::from(a ^ b)
+                    });
+                Ok(diff == 0)
+            });
+            // Deleted whether or not the seeds could be compared.
+            let deleted = client.vault(EPHEMERAL_VAULT).delete_secret(&check);
+            let matches = matches?;
+            deleted?;
+            if !matches {
+                return Err(Error::invalid_input(
+                    "oldPassphrase",
+                    "does not yield the seed at `seed` with this mnemonic",
+                ));
+            }
+            execute(
+                client,
+                StrongholdProcedure::<N>::BIP39Recover(BIP39Recover {
+                    mnemonic: Mnemonic::from(String::clone(&phrase)),
+                    passphrase: passphrase_from(new_passphrase),
+                    output: output.into(),
+                }),
+            )?;
+            Ok(Vec::new())
+        }
+        #[cfg(feature = "unsafe-export")]
+        ProcedureDto::ConvertMnemonicToSeed {
+            mnemonic,
+            passphrase,
+        } => {
+            let mut record = vec![0u8; 16];
+            crypto::utils::rand::fill(&mut record)?;
+            let seed = execute(
+                client,
+                StrongholdProcedure::<N>::BIP39Recover(BIP39Recover {
+                    mnemonic: Mnemonic::from(mnemonic.into_inner()),
+                    passphrase: passphrase_from(passphrase),
+                    output: Location::generic(EPHEMERAL_VAULT, record.clone()),
+                }),
+            )
+            .and_then(|_| {
+                client
+                    .vault(EPHEMERAL_VAULT)
+                    .read_secret(record.clone())
+                    .map_err(Into::into)
+            });
+            let deleted = client.vault(EPHEMERAL_VAULT).delete_secret(&record);
+            let seed = seed?;
+            deleted?;
+            Ok(seed.to_vec())
+        }
+        ProcedureDto::ValidateMnemonic { mnemonic, language } => {
+            output::to_bytes(&wordlist::validate(mnemonic.as_str(), language.as_deref())?)
+        }
+        ProcedureDto::Random { size_bytes } => {
+            let mut bytes = vec![0u8; size_bytes];
+            crypto::utils::rand::fill(&mut bytes)?;
+            Ok(bytes)
+        }
+        ProcedureDto::SLIP10Rotate {
+            curve,
+            chain,
+            input,
+            output,
+            network,
+        } => {
+            let (Slip10DeriveInputDto::Seed(source) | Slip10DeriveInputDto::Key(source)) = &input;
+            let LocationDto::Generic { vault, record } = source.clone() else {
+                return Err(Error::invalid_input(
+                    "input",
+                    "the rotated secret must be at a generic location",
+                ));
+            };
+            if Location::from(source.clone()) == Location::from(output.clone()) {
+                return Err(Error::invalid_input(
+                    "output",
+                    "must differ from the rotated secret",
+                ));
+            }
+            let chain_code = execute(
+                client,
+                StrongholdProcedure::<N>::Slip10Derive(Slip10Derive {
+                    curve,
+                    chain,
+                    input: input.into(),
+                    output: output.into(),
+                    network,
+                }),
+            )?;
+            client.vault(vault).delete_secret(record)?;
+            Ok(chain_code)
+        }
+        ProcedureDto::SLIP10DeriveString {
+            curve,
+            path,
+            seed,
+            network,
+            ty,
+            encoding,
+        } => {
+            let chain = derivation_path_from_string(&path)?;
+            let mut record = vec![0u8; 16];
+            crypto::utils::rand::fill(&mut record)?;
+            let derived = Location::generic(EPHEMERAL_VAULT, record.clone());
+            let public_key = execute(
+                client,
+                StrongholdProcedure::<N>::Slip10Derive(Slip10Derive {
+                    curve,
+                    chain,
+                    input: Slip10DeriveInput::Seed(seed.into()),
+                    output: derived.clone(),
+                    network,
+                }),
+            )
+            .and_then(|_| {
+                execute(
+                    client,
+                    StrongholdProcedure::<N>::PublicKey(PublicKey {
+                        ty: ty.into(),
+                        private_key: derived,
+                    }),
+                )
+            });
+            // Deleted whether or not the public key could be computed.
+            let deleted = client.vault(EPHEMERAL_VAULT).delete_secret(&record);
+            let public_key = public_key?;
+            deleted?;
+            Ok(encoding
+                .unwrap_or_default()
+                .encode(&public_key)
+                .into_bytes())
+        }
+        ProcedureDto::DeriveViewKeyFromSeed {
+            curve,
+            seed,
+            index,
+            network,
+            ext,
+        } => {
+            let chain = aleo_account_chain(index)?;
+            let mut record = vec![0u8; 16];
+            crypto::utils::rand::fill(&mut record)?;
+            let derived = Location::generic(EPHEMERAL_VAULT, record.clone());
+            let keys = execute(
+                client,
+                StrongholdProcedure::<N>::Slip10Derive(Slip10Derive {
+                    curve,
+                    chain,
+                    input: Slip10DeriveInput::Seed(seed.into()),
+                    output: derived.clone(),
+                    network,
+                }),
+            )
+            .and_then(|_| aleo_account_keys::<N>(client, derived, ext));
+            // Deleted whether or not the keys could be computed.
+            let deleted = client.vault(EPHEMERAL_VAULT).delete_secret(&record);
+            let keys = keys?;
+            deleted?;
+            output::to_bytes(&keys)
+        }
+        ProcedureDto::GenerateKey {
+            ty,
+            output,
+            return_public_key: true,
+        } => {
+            execute(
+                client,
+                StrongholdProcedure::<N>::GenerateKey(GenerateKey {
+                    ty: ty.into(),
+                    output: output.clone().into(),
+                }),
+            )?;
+            execute(
+                client,
+                StrongholdProcedure::<N>::PublicKey(PublicKey {
+                    ty: ty.into(),
+                    private_key: output.into(),
+                }),
+            )
+        }
+        ProcedureDto::ShamirSplit {
+            seed,
+            threshold,
+            shares,
+        } => {
+            let LocationDto::Generic { vault, record } = seed else {
+                return Err(Error::invalid_input(
+                    "seed",
+                    "must be at a generic location",
+                ));
+            };
+            let secret = client.vault(vault).read_secret(record)?;
+            let shares = shamir::split(&secret, threshold, shares)?;
+            let shares = Zeroizing::new(shares.iter().map(hex::encode).collect::<Vec<_>>());
+            output::to_bytes(&*shares)
+        }
+        ProcedureDto::ShamirCombine { shares, output } => {
+            let LocationDto::Generic { vault, .. } = &output else {
+                return Err(Error::invalid_input("output", "must be a generic location"));
+            };
+            let shares = shares
+                .into_iter()
+                .map(|share| {
+                    hex::decode(share.as_str())
+                        .map(Zeroizing::new)
+                        .map_err(|_| Error::invalid_input("shares", "a share is not hex encoded"))
+                })
+                .collect::<Result<Vec<_>>>()?;
+            let secret = shamir::combine(&shares)?;
+            client
+                .vault(vault.clone())
+                .write_secret(output.into(), secret)?;
+            Ok(Vec::new())
+        }
+        ProcedureDto::ImportAleoPrivateKey { key, output } => {
+            let LocationDto::Generic { vault, record } = &output else {
+                return Err(Error::invalid_input("output", "must be a generic location"));
+            };
+            if client.record_exists(&output.clone().into())? {
+                return Err(Error::RecordExists);
+            }
+            let private_key = PrivateKey::<N>::from_str(key.as_str())
+                .map_err(|e| Error::invalid_value("key", e.to_string()))?;
+            let view_key = ViewKey::try_from(&private_key)
+                .map_err(|e| Error::invalid_value("key", e.to_string()))?;
+            let seed = Zeroizing::new(
+                private_key
+                    .seed()
+                    .to_bytes_le()
+                    .map_err(|e| Error::invalid_value("key", e.to_string()))?,
+            );
+            let vault = client.vault(vault.clone());
+            vault.write_secret(output.clone().into(), seed.to_vec())?;
+            let imported = aleo_account_keys::<N>(client, output.into(), None);
+            if !matches!(&imported, Ok(keys) if keys.view_key == view_key.to_string()) {
+                vault.delete_secret(record)?;
+                imported?;
+                return Err(Error::invalid_input(
+                    "key",
+                    "is read back by the vault as another account",
+                ));
+            }
+            Ok(Vec::new())
+        }
+        ProcedureDto::GenerateEd25519AndRegister { output } => {
+            if client.record_exists(&output.clone().into())? {
+                return Err(Error::RecordExists);
+            }
+            execute(
+                client,
+                StrongholdProcedure::<N>::GenerateKey(GenerateKey {
+                    ty: KeyType::Ed25519.into(),
+                    output: output.clone().into(),
+                }),
+            )?;
+            execute(
+                client,
+                StrongholdProcedure::<N>::PublicKey(PublicKey {
+                    ty: KeyType::Ed25519.into(),
+                    private_key: output.into(),
+                }),
+            )
+        }
+        ProcedureDto::GetPublicKeyString {
+            ty,
+            private_key,
+            encoding,
+        } => {
+            let public_key = execute(
+                client,
+                StrongholdProcedure::<N>::PublicKey(PublicKey {
+                    ty: ty.into(),
+                    private_key: private_key.into(),
+                }),
+            )?;
+            Ok(encoding
+                .unwrap_or_default()
+                .encode(&public_key)
+                .into_bytes())
+        }
+        ProcedureDto::GetPublicIdentity {
+            ed25519,
+            x25519,
+            aleo,
+            encoding,
+        } => {
+            if ed25519.is_none() && x25519.is_none() && aleo.is_none() {
+                return Err(Error::invalid_input(
+                    "payload",
+                    "at least one private key location is required",
+                ));
+            }
+            let encoding = encoding.unwrap_or_default();
+            let public_key = |ty: StrongholdKeyType, private_key: LocationDto| {
+                execute(
+                    client,
+                    StrongholdProcedure::<N>::PublicKey(PublicKey {
+                        ty,
+                        private_key: private_key.into(),
+                    }),
+                )
+                .map(|key| encoding.encode(&key))
+            };
+            let aleo_address = aleo
+                .map(|(private_key, ext)| {
+                    let address = execute(
+                        client,
+                        StrongholdProcedure::GetAleoAddress(GetAleoAddress {
+                            private_key: private_key.into(),
+                            ext,
+                        }),
+                    )?;
+                    String::from_utf8(address).map_err(|_| {
+                        Error::invalid_input("aleo", "the runtime returned a non UTF-8 address")
+                    })
+                })
+                .transpose()?;
+            output::to_bytes(&PublicIdentity {
+                ed25519: ed25519
+                    .map(|location| public_key(StrongholdKeyType::Ed25519, location))
+                    .transpose()?,
+                x25519: x25519
+                    .map(|location| public_key(StrongholdKeyType::X25519, location))
+                    .transpose()?,
+                aleo_address,
+            })
+        }
+        ProcedureDto::Ed25519SignStructured { private_key, msg } => {
+            let signature = execute(
+                client,
+                StrongholdProcedure::<N>::Ed25519Sign(Ed25519Sign {
+                    private_key: private_key.clone().into(),
+                    msg: msg.as_bytes().to_vec(),
+                }),
+            )?;
+            let public_key = execute(
+                client,
+                StrongholdProcedure::<N>::PublicKey(PublicKey {
+                    ty: StrongholdKeyType::Ed25519,
+                    private_key: private_key.into(),
+                }),
+            )?;
+            output::to_bytes(&SignatureOutput::new(
+                SignatureAlgorithm::Ed25519,
+                &signature,
+                &public_key,
+            ))
+        }
+        ProcedureDto::VerifyEd25519 {
+            public_key,
+            msg,
+            signature,
+        } => {
+            let public_key = match public_key {
+                Ed25519PublicKeyDto::Bytes(bytes) => bytes,
+                Ed25519PublicKeyDto::Location(location) => execute(
+                    client,
+                    StrongholdProcedure::<N>::PublicKey(PublicKey {
+                        ty: StrongholdKeyType::Ed25519,
+                        private_key: location.into(),
+                    }),
+                )?,
+            };
+            let public_key = <[u8; ed25519::PUBLIC_KEY_LENGTH]>::try_from(public_key)
+                .ok()
+                .and_then(|bytes| ed25519::PublicKey::try_from_bytes(bytes).ok())
+                .ok_or_else(|| Error::invalid_input("publicKey", "is not an Ed25519 public key"))?;
+            let signature = <[u8; ed25519::SIGNATURE_LENGTH]>::try_from(signature)
+                .map(ed25519::Signature::from_bytes)
+                .map_err(|_| {
+                    Error::invalid_input(
+                        "signature",
+                        format!("must be {} bytes long", ed25519::SIGNATURE_LENGTH),
+                    )
+                })?;
+            output::to_bytes(&public_key.verify(&signature, msg.as_ref()))
+        }
+        ProcedureDto::AleoSignStructured {
+            private_key,
+            msg,
+            ext,
+        } => {
+            let signature = execute(
+                client,
+                StrongholdProcedure::AleoSign(AleoSign {
+                    private_key: private_key.clone().into(),
+                    msg: msg.as_bytes().to_vec(),
+                    ext,
+                }),
+            )?;
+            let address = execute(
+                client,
+                StrongholdProcedure::GetAleoAddress(GetAleoAddress {
+                    private_key: private_key.into(),
+                    ext,
+                }),
+            )?;
+            output::to_bytes(&SignatureOutput::new(
+                SignatureAlgorithm::Aleo,
+                &signature,
+                &address,
+            ))
+        }
+        ProcedureDto::SignMany {
+            algorithm,
+            private_key,
+            messages,
+            ext,
+        } => {
+            let signatures = messages
+                .into_iter()
+                .map(|msg| {
+                    let private_key = private_key.clone().into();
+                    let procedure = match (algorithm, ext) {
+                        (SignatureAlgorithm::Ed25519, _) => {
+                            StrongholdProcedure::<N>::Ed25519Sign(Ed25519Sign {
+                                private_key,
+                                msg: msg.into(),
+                            })
+                        }
+                        (SignatureAlgorithm::Aleo, Some(ext)) => {
+                            StrongholdProcedure::AleoSign(AleoSign {
+                                private_key,
+                                msg: msg.into(),
+                                ext,
+                            })
+                        }
+                        (SignatureAlgorithm::Aleo, None) => {
+                            return Err(Error::invalid_input(
+                                "ext",
+                                "is required to sign with Aleo",
+                            ))
+                        }
+                    };
+                    execute(client, procedure).map(hex::encode)
+                })
+                .collect::<Result<Vec<_>>>()?;
+            output::to_bytes(&signatures)
+        }
+        ProcedureDto::AleoScanRecords {
+            private_key,
+            ciphertexts,
+        } => {
+            let view_key = Zeroizing::new(execute(
+                client,
+                StrongholdProcedure::<N>::GetAleoViewKey(GetAleoViewKey {
+                    private_key: private_key.into(),
+                    _network: PhantomData,
+                }),
+            )?);
+            let view_key = std::str::from_utf8(&view_key)
+                .map_err(|e| e.to_string())
+                .and_then(|view_key| ViewKey::<N>::from_str(view_key).map_err(|e| e.to_string()))
+                .map_err(|e| Error::invalid_value("privateKey", e))?;
+            let records = ciphertexts
+                .iter()
+                .enumerate()
+                .filter(|(_, ciphertext)| ciphertext.is_owner(&view_key))
+                .map(|(index, ciphertext)| {
+                    let record = ciphertext.decrypt(&view_key).map_err(|e| {
+                        Error::invalid_value(format!("ciphertexts[{index}]"), e.to_string())
+                    })?;
+                    Ok(DecryptedRecord {
+                        index,
+                        record: record.to_string(),
+                    })
+                })
+                .collect::<Result<Vec<_>>>()?;
+            output::to_bytes(&records)
+        }
+        ProcedureDto::AleoSignRequests {
+            mut transitions,
+            private_key,
+        } => {
+            let sign = |transition: TransitionDto<N>, root_tvk: Option<Field<N>>| {
+                let is_root = root_tvk.is_none();
+                let request = execute(
+                    client,
+                    StrongholdProcedure::AleoSignRequest(AleoSignRequest {
+                        program_id: transition.program_id,
+                        function_name: transition.function_name,
+                        inputs: transition.inputs,
+                        input_types: transition.input_types,
+                        root_tvk,
+                        is_root,
+                        private_key: private_key.clone().into(),
+                    }),
+                )?;
+                output::parse_request::<N>(&request)
+            };
+            let root = transitions
+                .iter()
+                .position(|t| t.is_root)
+                .ok_or_else(|| Error::invalid_input("transitions", "none is the root"))?;
+            let root_request = sign(transitions.remove(root), None)?;
+            let root_tvk = *root_request.tvk();
+            let mut requests = transitions
+                .into_iter()
+                .map(|t| sign(t, Some(root_tvk)).map(|request| request.to_string()))
+                .collect::<Result<Vec<_>>>()?;
+            requests.insert(root, root_request.to_string());
+            output::to_bytes(&requests)
+        }
+        ProcedureDto::AleoCombineAuthorizations {
+            authorization,
+            fee_authorization,
+        } => output::to_bytes(&sponsor::combine_authorizations::<N>(
+            &authorization,
+            &fee_authorization,
+        )?),
+        procedure => {
+            let format = procedure.authorization_format();
+            let output = execute(client, StrongholdProcedure::try_from(procedure)?)?;
+            match format {
+                AuthorizationFormat::Raw => Ok(output),
+                AuthorizationFormat::Json => output::authorization_to_json::<N>(&output),
+            }
+        }
+    }
+}
+
+/// The view key and, for the domain separator `ext`, the address of the Aleo private key at
+/// `private_key`.
+fn aleo_account_keys<N: Network>(
+    client: &Client,
+    private_key: Location,
+    ext: Option<Identifier<N>>,
+) -> Result<AleoAccountKeys> {
+    let view_key = Zeroizing::new(execute(
+        client,
+        StrongholdProcedure::<N>::GetAleoViewKey(GetAleoViewKey {
+            private_key: private_key.clone(),
+            _network: PhantomData,
+        }),
+    )?);
+    let view_key = std::str::from_utf8(&view_key)
+        .map_err(|e| e.to_string())
+        .and_then(|view_key| ViewKey::<N>::from_str(view_key).map_err(|e| e.to_string()))
+        .map_err(|e| Error::invalid_value("seed", e))?;
+    let address = ext
+        .map(|ext| {
+            let address = execute(
+                client,
+                StrongholdProcedure::GetAleoAddress(GetAleoAddress { private_key, ext }),
+            )?;
+            std::str::from_utf8(&address)
+                .map_err(|e| e.to_string())
+                .and_then(|address| Address::<N>::from_str(address).map_err(|e| e.to_string()))
+                .map(|address| address.to_string())
+                .map_err(|e| Error::invalid_value("ext", e))
+        })
+        .transpose()?;
+    Ok(AleoAccountKeys {
+        view_key: view_key.to_string(),
+        address,
+    })
+}
+
+fn execute<N: Network>(client: &Client, procedure: StrongholdProcedure<N>) -> Result<Vec<u8>> {
+    client
+        .execute_procedure(procedure)
+        .map(Into::into)
+        .map_err(Into::into)
+}
+
+fn open_stronghold(
+    collection: &StrongholdCollection,
+    snapshot_path: &SnapshotPath,
+    hash: Vec<u8>,
+) -> Result<Stronghold> {
+    #[cfg(feature = "mlock")]
+    if collection.config.lock_memory {
+        mlock::lock_process_memory();
+    }
+    let stronghold = match &collection.config.snapshot_store {
+        Some(store) => Stronghold::with_store(snapshot_path, hash, store.clone()),
+        None => Stronghold::with_backend(snapshot_path, hash, collection.config.backend),
+    };
+    // Only a failure to decrypt hints at a wrong password.
+    match &stronghold {
+        Ok(_) => collection.attempts.record(snapshot_path, true),
+        Err(Error::Stronghold(_)) => collection.attempts.record(snapshot_path, false),
+        Err(_) => {}
+    }
+    stronghold?
+        .with_max_size(collection.config.max_snapshot_size)
+        .with_temp_dir(collection.config.temp_dir.clone())
+        .with_file_mode(collection.config.file_mode)
+}
+
+/// Runs `f` on a loaded snapshot while holding its lock.
+fn with_stronghold<T>(
+    collection: &StrongholdCollection,
+    snapshot_path: &SnapshotPath,
+    f: impl FnOnce(&Stronghold) -> Result<T>,
+) -> Result<T> {
+    let stronghold =
+        find_stronghold(collection, snapshot_path).ok_or(Error::StrongholdNotInitialized)?;
+    let stronghold = stronghold.lock().unwrap();
+    f(&stronghold)
+}
+
+/// Updates the record index of `client` after a procedure wrote or deleted secrets.
+fn index_records(
+    collection: &StrongholdCollection,
+    snapshot_path: &SnapshotPath,
+    client: &ClientPath,
+    changes: Vec<(LocationDto, bool)>,
+) -> Result<()> {
+    if changes.is_empty() {
+        return Ok(());
+    }
+    with_stronghold(collection, snapshot_path, |stronghold| {
+        for (location, present) in changes {
+            // Counter locations have no record path to list.
+            if let LocationDto::Generic { vault, record } = location {
+                stronghold.index_record(
+                    client.as_ref(),
+                    vault.as_ref(),
+                    record.as_ref(),
+                    present,
+                )?;
+            }
+        }
+        Ok(())
+    })?;
+    mark_dirty(collection, snapshot_path);
+    Ok(())
+}
+
+/// Records an unsaved write to the snapshot, and schedules a save if saves are debounced.
+fn mark_dirty(collection: &StrongholdCollection, snapshot_path: &SnapshotPath) {
+    if let Some(stronghold) = find_stronghold(collection, snapshot_path) {
+        stronghold.lock().unwrap().mark_dirty();
+    }
+    if let Some(debouncer) = &collection.debouncer {
+        debouncer.mark_dirty(snapshot_path);
+    }
+}
+
+/// The operation to report to the [`Builder::audit_sink`], built only if there is one.
+fn audit_operation(
+    collection: &StrongholdCollection,
+    operation: impl FnOnce() -> AuditOperation,
+) -> Option<AuditOperation> {
+    collection.audit_log.as_ref().map(|_| operation())
+}
+
+fn audit_procedure<N: Network>(procedure: &ProcedureDto<N>) -> AuditOperation {
+    AuditOperation::Procedure {
+        name: procedure.name().to_string(),
+        locations: procedure.locations().into_iter().cloned().collect(),
+    }
+}
+
+/// Reports `operation` on `client` to the audit log, whether `result` is a success or not,
+/// and passes `result` on.
+fn audited<T>(
+    collection: &StrongholdCollection,
+    snapshot_path: &SnapshotPath,
+    client: &ClientPath,
+    operation: Option<AuditOperation>,
+    result: Result<T>,
+) -> Result<T> {
+    if let (Some(audit_log), Some(operation)) = (&collection.audit_log, operation) {
+        audit_log.record(snapshot_path, client, operation, result.is_ok());
+    }
+    result
+}
+
+/// Accounts for a write of `bytes` against the size limit of the snapshot.
+fn reserve(
+    collection: &StrongholdCollection,
+    snapshot_path: &SnapshotPath,
+    bytes: usize,
+) -> Result<()> {
+    find_stronghold(collection, snapshot_path)
+        .ok_or(Error::StrongholdNotInitialized)?
+        .lock()
+        .unwrap()
+        .reserve(bytes as u64)
+}
+
+fn validate_path(kind: &str, path: impl AsRef<[u8]>, max_path_length: usize) -> Result<()> {
+    let len = path.as_ref().len();
+    if len == 0 {
+        return Err(Error::invalid_input(kind, "path must not be empty"));
+    }
+    if len > max_path_length {
+        return Err(Error::invalid_input(
+            kind,
+            format!("path is {len} bytes long, the maximum is {max_path_length}"),
+        ));
+    }
+    Ok(())
+}
+
+fn validate_vault_and_record(
+    collection: &StrongholdCollection,
+    vault: &VaultPath,
+    record_path: &RecordPath,
+) -> Result<()> {
+    validate_path("vault", vault, collection.config.max_path_length)?;
+    validate_path("record", record_path, collection.config.max_path_length)
+}
+
+/// Looks up a loaded snapshot, holding the collection lock only for the lookup.
+fn find_stronghold(
+    collection: &StrongholdCollection,
+    snapshot_path: &SnapshotPath,
+) -> Option<Arc<Mutex<Stronghold>>> {
+    collection
+        .snapshots
+        .lock()
+        .unwrap()
+        .get(snapshot_path)
+        .cloned()
+}
+
+/// Puts back a snapshot that failed to be unloaded, unless it was initialized again meanwhile.
+fn restore_stronghold(
+    collection: &StrongholdCollection,
+    snapshot_path: SnapshotPath,
+    stronghold: Arc<Mutex<Stronghold>>,
+) {
+    collection
+        .snapshots
+        .lock()
+        .unwrap()
+        .entry(snapshot_path)
+        .or_insert(stronghold);
+}
+
+/// Returns a handle of `client`, holding the snapshot lock only while looking it up.
+fn get_client(
+    collection: &StrongholdCollection,
+    snapshot_path: SnapshotPath,
+    client: ClientPath,
+) -> Result<Client> {
+    find_client(collection, &snapshot_path, client)?.ok_or(Error::StrongholdNotInitialized)
+}
+
+/// [`get_client`], with `None` if the snapshot isn't initialized.
+fn find_client(
+    collection: &StrongholdCollection,
+    snapshot_path: &SnapshotPath,
+    client: ClientPath,
+) -> Result<Option<Client>> {
+    let Some(stronghold) = find_stronghold(collection, snapshot_path) else {
+        return Ok(None);
+    };
+    let client = stronghold.lock().unwrap().client(client);
+    client.map(Some)
+}
+
+pub enum PasswordHashFunctionKind {
     #[cfg(feature = "kdf")]
     Argon2(PathBuf),
     Custom(Box<PasswordHashFn>),
+    /// One hash function per password, their hashes folded into a single key, see
+    /// [`initialize_combined`].
+    Combined(Vec<Box<PasswordHashFn>>),
+    /// Key material from a hardware token instead of a password, see
+    /// [`initialize_with_hardware_key`].
+    #[cfg(feature = "hardware-key")]
+    Hardware(Box<HardwareKeyFn>),
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{
+        fs,
+        future::Future,
+        pin::pin,
+        task::{Context, Poll, Wake, Waker},
+        thread::{self, Thread},
+    };
+
+    use crypto::hashes::sha::SHA256;
+    use serde_json::{json, Value};
+    use snarkvm_console::network::MainnetV0;
+
+    use super::*;
+
+    struct ThreadWaker(Thread);
+
+    impl Wake for ThreadWaker {
+        fn wake(self: Arc<Self>) {
+            self.0.unpark();
+        }
+    }
+
+    fn block_on<F: Future>(future: F) -> F::Output {
+        let mut future = pin!(future);
+        let waker = Waker::from(Arc::new(ThreadWaker(thread::current())));
+        let mut cx = Context::from_waker(&waker);
+        loop {
+            match future.as_mut().poll(&mut cx) {
+                Poll::Ready(output) => return output,
+                Poll::Pending => thread::park(),
+            }
+        }
+    }
+
+    fn hash_function() -> PasswordHashFunction {
+        PasswordHashFunction(Box::new(|password| {
+            let mut hash = [0u8; 32];
+            SHA256(password.as_bytes(), &mut hash);
+            hash.to_vec()
+        }))
+    }
+
+    /// A temporary directory, removed on drop.
+    struct TempDir(PathBuf);
+
+    impl TempDir {
+        fn new() -> Self {
+            let dir = std::env::temp_dir().join(format!(
+                "stronghold-test-{}",
+                hex::encode(rand::random::<[u8; 16]>())
+            ));
+            fs::create_dir(&dir).unwrap();
+            Self(dir)
+        }
+    }
+
+    impl Drop for TempDir {
+        fn drop(&mut self) {
+            let _ = fs::remove_dir_all(&self.0);
+        }
+    }
+
+    /// A collection with one snapshot initialized in a temporary directory and its `client`
+    /// created.
+    struct Fixture {
+        collection: StrongholdCollection,
+        path: SnapshotPath,
+        client: ClientPath,
+        // Dropped last, after the collection saved its snapshots.
+        _dir: TempDir,
+    }
+
+    impl Fixture {
+        fn new() -> Self {
+            let dir = TempDir::new();
+            let fixture = Self {
+                collection: StrongholdCollection::default(),
+                path: dir.0.join("test.hold").into(),
+                client: "client".into(),
+                _dir: dir,
+            };
+            fixture.initialize();
+            block_on(create_client(
+                &fixture.collection,
+                fixture.path.clone(),
+                fixture.client.clone(),
+            ))
+            .unwrap();
+            fixture
+        }
+
+        fn initialize(&self) {
+            block_on(initialize(
+                &self.collection,
+                hash_function(),
+                self.path.clone(),
+                "password".to_string(),
+            ))
+            .unwrap();
+        }
+
+        /// Initializes another snapshot `file_name` in the same directory and collection, with
+        /// `client` created.
+        fn open_snapshot(&self, file_name: &str, client: &str) -> SnapshotPath {
+            let path = SnapshotPath::from(self._dir.0.join(file_name));
+            block_on(initialize(
+                &self.collection,
+                hash_function(),
+                path.clone(),
+                "other password".to_string(),
+            ))
+            .unwrap();
+            block_on(create_client(&self.collection, path.clone(), client.into())).unwrap();
+            path
+        }
+
+        /// Unloads the snapshot, saving it, then opens it again from its file.
+        fn reload(&self) {
+            block_on(destroy(&self.collection, self.path.clone())).unwrap();
+            self.initialize();
+            block_on(load_client(
+                &self.collection,
+                self.path.clone(),
+                self.client.clone(),
+            ))
+            .unwrap();
+        }
+
+        fn save(&self, key: &str, value: &[u8]) {
+            block_on(save_store_record(
+                &self.collection,
+                self.path.clone(),
+                self.client.clone(),
+                key.to_string(),
+                value.to_vec(),
+                None,
+            ))
+            .unwrap();
+        }
+
+        fn get(&self, key: &str) -> Option<Vec<u8>> {
+            block_on(get_store_record(
+                &self.collection,
+                self.path.clone(),
+                self.client.clone(),
+                key.to_string(),
+            ))
+            .unwrap()
+        }
+    }
+
+    /// Deserializes `value`, serializes the result back and checks nothing changed.
+    fn round_trip<T: Serialize + for<'de> Deserialize<'de>>(value: Value) -> T {
+        let dto: T = serde_json::from_value(value.clone()).unwrap();
+        assert_eq!(serde_json::to_value(&dto).unwrap(), value);
+        dto
+    }
+
+    #[test]
+    fn location_dto_round_trips() {
+        let generic: LocationDto = round_trip(json!({
+            "type": "Generic",
+            "payload": { "vault": "vault", "record": [1, 2, 3] },
+        }));
+        assert!(matches!(
+            generic,
+            LocationDto::Generic {
+                vault: BytesDto::Text(_),
+                record: BytesDto::Raw(_),
+            }
+        ));
+        let counter: LocationDto = round_trip(json!({
+            "type": "Counter",
+            "payload": { "vault": "vault", "counter": 4 },
+        }));
+        assert!(matches!(counter, LocationDto::Counter { counter: 4, .. }));
+    }
+
+    #[test]
+    fn slip10_derive_input_dto_round_trips() {
+        for kind in ["Seed", "Key"] {
+            let input: Slip10DeriveInputDto = round_trip(json!({
+                "type": kind,
+                "payload": {
+                    "type": "Generic",
+                    "payload": { "vault": "vault", "record": "record" },
+                },
+            }));
+            let cloned = input.clone();
+            assert_eq!(
+                serde_json::to_value(cloned).unwrap(),
+                serde_json::to_value(input).unwrap()
+            );
+        }
+    }
+
+    #[test]
+    fn location_dto_rejects_unknown_variants() {
+        let location = json!({ "type": "Sequence", "payload": { "vault": "vault" } });
+        assert!(serde_json::from_value::<LocationDto>(location).is_err());
+    }
+
+    const STORE_KEYS: [&str; 6] = [
+        "theme",
+        "settings:theme",
+        "with space",
+        // The same text, composed and decomposed: the bytes differ, and so do the keys.
+        "th\u{e8}me",
+        "the\u{300}me",
+        "🔑",
+    ];
+
+    #[test]
+    fn store_keys_read_back_with_the_same_string() {
+        let fixture = Fixture::new();
+        for (i, key) in STORE_KEYS.iter().enumerate() {
+            fixture.save(key, &[i as u8]);
+        }
+        for (i, key) in STORE_KEYS.iter().enumerate() {
+            assert_eq!(fixture.get(key), Some(vec![i as u8]), "{key}");
+        }
+        assert_eq!(fixture.get("Theme"), None);
+        assert_eq!(fixture.get("theme "), None);
+    }
+
+    #[test]
+    fn store_keys_read_back_after_reloading_the_snapshot() {
+        let fixture = Fixture::new();
+        for (i, key) in STORE_KEYS.iter().enumerate() {
+            fixture.save(key, &[i as u8]);
+        }
+        fixture.reload();
+        for (i, key) in STORE_KEYS.iter().enumerate() {
+            assert_eq!(fixture.get(key), Some(vec![i as u8]), "{key}");
+        }
+    }
+
+    #[test]
+    fn writes_through_a_client_handle_are_saved_once_marked() {
+        let fixture = Fixture::new();
+        block_on(save(&fixture.collection, fixture.path.clone())).unwrap();
+        let client = block_on(try_get_client(
+            &fixture.collection,
+            fixture.path.clone(),
+            fixture.client.clone(),
+        ))
+        .unwrap()
+        .unwrap();
+        client
+            .store()
+            .insert(b"handle".to_vec(), b"value".to_vec(), None)
+            .unwrap();
+        block_on(mark_snapshot_dirty(
+            &fixture.collection,
+            fixture.path.clone(),
+        ))
+        .unwrap();
+        block_on(save(&fixture.collection, fixture.path.clone())).unwrap();
+
+        fixture.reload();
+        assert_eq!(fixture.get("handle"), Some(b"value".to_vec()));
+    }
+
+    #[test]
+    fn concurrent_writes_and_saves_are_not_lost() {
+        const THREADS: usize = 4;
+        const WRITES: usize = 10;
+
+        let fixture = Arc::new(Fixture::new());
+        let record = |thread: usize, i: usize| format!("record-{thread}-{i}");
+        let key = |thread: usize, i: usize| format!("key-{thread}-{i}");
+
+        let (done, finished) = mpsc::channel();
+        let worker = Arc::clone(&fixture);
+        thread::spawn(move || {
+            let fixture = &*worker;
+            thread::scope(|scope| {
+                for t in 0..THREADS {
+                    scope.spawn(move || {
+                        for i in 0..WRITES {
+                            fixture.save(&key(t, i), i.to_string().as_bytes());
+                        }
+                    });
+                    scope.spawn(move || {
+                        for i in 0..WRITES {
+                            let procedure = ProcedureDto::<MainnetV0>::SLIP10Generate {
+                                output: LocationDto::Generic {
+                                    vault: BytesDto::Text("vault".into()),
+                                    record: BytesDto::Text(record(t, i)),
+                                },
+                                size_bytes: None,
+                            };
+                            block_on(execute_procedure(
+                                &fixture.collection,
+                                fixture.path.clone(),
+                                fixture.client.clone(),
+                                procedure,
+                            ))
+                            .unwrap();
+                        }
+                    });
+                    scope.spawn(move || {
+                        for _ in 0..WRITES {
+                            block_on(save(&fixture.collection, fixture.path.clone())).unwrap();
+                        }
+                    });
+                }
+            });
+            done.send(()).unwrap();
+        });
+        finished
+            .recv_timeout(Duration::from_secs(120))
+            .expect("concurrent operations deadlocked or panicked");
+
+        let check = |fixture: &Fixture| {
+            for t in 0..THREADS {
+                for i in 0..WRITES {
+                    assert_eq!(
+                        fixture.get(&key(t, i)),
+                        Some(i.to_string().into_bytes()),
+                        "{}",
+                        key(t, i)
+                    );
+                }
+            }
+            let records = block_on(list_vault_records(
+                &fixture.collection,
+                fixture.path.clone(),
+                fixture.client.clone(),
+                "vault".into(),
+                PageRequest::ALL,
+            ))
+            .unwrap();
+            assert_eq!(records.total, THREADS * WRITES);
+        };
+        check(&fixture);
+
+        // Everything must also have reached the snapshot file.
+        fixture.reload();
+        check(&fixture);
+    }
+
+    #[test]
+    fn timed_out_request_keeps_its_idempotency_key_in_flight() {
+        let collection = StrongholdCollection::default();
+        let path = SnapshotPath::from("idempotency.hold");
+        let client = ClientPath::from("client");
+        let runs = Arc::new(std::sync::atomic::AtomicUsize::new(0));
+        let run = |timeout| {
+            let runs = Arc::clone(&runs);
+            run_deduplicated_with_timeout(
+                &collection,
+                &path,
+                &client,
+                Some("key".into()),
+                None,
+                Some(timeout),
+                move || {
+                    runs.fetch_add(1, std::sync::atomic::Ordering::SeqCst);
+                    thread::sleep(Duration::from_millis(300));
+                    Ok(b"output".to_vec())
+                },
+            )
+        };
+
+        let first = run(Duration::from_millis(10));
+        assert!(matches!(first, Err(Error::Timeout(_))));
+        let retry = run(Duration::from_secs(10));
+        assert!(matches!(retry, Err(Error::DuplicateRequest(_))));
+
+        thread::sleep(Duration::from_millis(600));
+        assert_eq!(run(Duration::from_secs(10)).unwrap(), b"output");
+        assert_eq!(runs.load(std::sync::atomic::Ordering::SeqCst), 1);
+    }
+
+    #[test]
+    fn sign_many_checks_ext_only_with_aleo() {
+        let sign_many = |algorithm| ProcedureDto::<MainnetV0>::SignMany {
+            algorithm,
+            private_key: LocationDto::Generic {
+                vault: BytesDto::Text("vault".into()),
+                record: BytesDto::Text("record".into()),
+            },
+            messages: Vec::new(),
+            ext: Some(Identifier::from_str("sign").unwrap()),
+        };
+        let config = Config::default();
+        assert!(sign_many(SignatureAlgorithm::Ed25519)
+            .validate(&config)
+            .is_ok());
+        assert!(matches!(
+            sign_many(SignatureAlgorithm::Aleo).validate(&config),
+            Err(Error::InvalidInput { .. })
+        ));
+    }
+
+    #[test]
+    fn batch_on_a_missing_client_fails_at_the_first_procedure() {
+        let fixture = Fixture::new();
+        let procedure = ProcedureDto::<MainnetV0>::SignMany {
+            algorithm: SignatureAlgorithm::Ed25519,
+            private_key: LocationDto::Generic {
+                vault: BytesDto::Text("vault".into()),
+                record: BytesDto::Text("record".into()),
+            },
+            messages: Vec::new(),
+            ext: None,
+        };
+        let result = block_on(execute_procedures(
+            &fixture.collection,
+            fixture.path.clone(),
+            "missing".into(),
+            vec![procedure],
+        ));
+        assert!(matches!(
+            result,
+            Err(Error::BatchProcedure { index: 0, .. })
+        ));
+    }
+
+    #[test]
+    fn import_into_an_occupied_location_keeps_the_old_secret() {
+        let fixture = Fixture::new();
+        block_on(save_secret(
+            &fixture.collection,
+            fixture.path.clone(),
+            fixture.client.clone(),
+            "vault".into(),
+            "record".into(),
+            b"old".to_vec(),
+            false,
+        ))
+        .unwrap();
+        let key = PrivateKey::<MainnetV0>::new(&mut rand::thread_rng()).unwrap();
+        let procedure = ProcedureDto::<MainnetV0>::ImportAleoPrivateKey {
+            key: key.to_string().into(),
+            output: LocationDto::Generic {
+                vault: BytesDto::Text("vault".into()),
+                record: BytesDto::Text("record".into()),
+            },
+        };
+        let result = block_on(execute_procedure(
+            &fixture.collection,
+            fixture.path.clone(),
+            fixture.client.clone(),
+            procedure,
+        ));
+        assert!(matches!(result, Err(Error::RecordExists)));
+
+        let client = block_on(try_get_client(
+            &fixture.collection,
+            fixture.path.clone(),
+            fixture.client.clone(),
+        ))
+        .unwrap()
+        .unwrap();
+        let secret = client.vault("vault").read_secret("record").unwrap();
+        assert_eq!(&*secret, b"old");
+    }
+
+    #[test]
+    fn migration_keeps_the_clients_of_the_target() {
+        let fixture = Fixture::new();
+        fixture.save("kept", b"target");
+        let source = fixture.open_snapshot("source.hold", "moved");
+        block_on(save_store_record(
+            &fixture.collection,
+            source.clone(),
+            "moved".into(),
+            "moved".into(),
+            b"source".to_vec(),
+            None,
+        ))
+        .unwrap();
+        block_on(save_secret(
+            &fixture.collection,
+            source.clone(),
+            "moved".into(),
+            "vault".into(),
+            "record".into(),
+            b"secret".to_vec(),
+            false,
+        ))
+        .unwrap();
+
+        block_on(migrate_between_snapshots(
+            &fixture.collection,
+            source,
+            fixture.path.clone(),
+            vec!["moved".into()],
+        ))
+        .unwrap();
+
+        let check = |fixture: &Fixture| {
+            assert_eq!(fixture.get("kept"), Some(b"target".to_vec()));
+            let moved = block_on(get_store_record(
+                &fixture.collection,
+                fixture.path.clone(),
+                "moved".into(),
+                "moved".into(),
+            ))
+            .unwrap();
+            assert_eq!(moved, Some(b"source".to_vec()));
+            let records = block_on(list_vault_records(
+                &fixture.collection,
+                fixture.path.clone(),
+                "moved".into(),
+                "vault".into(),
+                PageRequest::ALL,
+            ))
+            .unwrap();
+            assert_eq!(records.items, vec![b"record".to_vec()]);
+        };
+        check(&fixture);
+
+        fixture.reload();
+        block_on(load_client(
+            &fixture.collection,
+            fixture.path.clone(),
+            "moved".into(),
+        ))
+        .unwrap();
+        check(&fixture);
+    }
+
+    #[test]
+    fn concurrent_registrations_create_one_key() {
+        let fixture = Fixture::new();
+        let results: Vec<_> = thread::scope(|scope| {
+            let handles: Vec<_> = (0..4)
+                .map(|_| {
+                    scope.spawn(|| {
+                        block_on(execute_procedure(
+                            &fixture.collection,
+                            fixture.path.clone(),
+                            fixture.client.clone(),
+                            ProcedureDto::<MainnetV0>::GenerateEd25519AndRegister {
+                                output: LocationDto::Generic {
+                                    vault: BytesDto::Text("vault".into()),
+                                    record: BytesDto::Text("identity".into()),
+                                },
+                            },
+                        ))
+                    })
+                })
+                .collect();
+            handles.into_iter().map(|h| h.join().unwrap()).collect()
+        });
+        assert_eq!(results.iter().filter(|result| result.is_ok()).count(), 1);
+        for result in results {
+            match result {
+                Ok(public_key) => assert_eq!(public_key.len(), 32),
+                Err(e) => assert!(matches!(e, Error::RecordExists), "{e}"),
+            }
+        }
+    }
 }
@@ -0,0 +1,40 @@
+// Copyright 2019-2023 Tauri Programme within The Commons Conservancy
+// SPDX-License-Identifier: Apache-2.0
+// SPDX-License-Identifier: MIT
+
+//! Keeps the process memory, and with it the decrypted snapshot, out of swap.
+//!
+//! Supported on Unix through `mlockall(2)`, which locks every current and future page of the
+//! process. The amount of lockable memory is bounded by `RLIMIT_MEMLOCK` (see `ulimit -l`),
+//! which is small by default on many Linux distributions; when the limit is hit the call
+//! fails and a warning is logged. Other platforms only log the warning.
+
+use std::sync::OnceLock;
+
+static LOCKED: OnceLock<bool> = OnceLock::new();
+
+/// Locks the pages of the process in RAM and returns whether it succeeded.
+///
+/// Only the first call does any work, later ones return its result.
+pub fn lock_process_memory() -> bool {
+    *LOCKED.get_or_init(lock)
+}
+
+#[cfg(unix)]
+fn lock() -> bool {
+    // SAFETY: mlockall takes no pointers and only changes the paging behavior of the process.
+    let ret = unsafe { libc::mlockall(libc::MCL_CURRENT | libc::MCL_FUTURE) };
+    if ret != 0 {
+        log::warn!(
+            "failed to lock process memory, secrets may be swapped to disk: {}",
+            std::io::Error::last_os_error()
+        );
+    }
+    ret == 0
+}
+
+#[cfg(not(unix))]
+fn lock() -> bool {
+    log::warn!("locking process memory is not supported on this platform");
+    false
+}
@@ -0,0 +1,175 @@
+// Copyright 2019-2023 Tauri Programme within The Commons Conservancy
+// SPDX-License-Identifier: Apache-2.0
+// SPDX-License-Identifier: MIT
+
+//! Structured outputs returned, JSON encoded, by the composite procedures.
+
+use serde::{Deserialize, Serialize};
+use snarkvm_console::{network::Network, prelude::FromBytes, program::Request};
+use snarkvm_synthesizer::process::Authorization;
+
+use crate::stronghold::Result;
+
+/// Current version of [`SignatureOutput`]. Bumped whenever its layout changes.
+pub const SIGNATURE_OUTPUT_VERSION: u8 = 1;
+
+#[derive(Deserialize, Serialize, Clone, Copy, Debug, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum SignatureAlgorithm {
+    Ed25519,
+    Aleo,
+}
+
+/// A detached signature with the public key that verifies it, both hex encoded: raw bytes for
+/// Ed25519, the UTF-8 `sign1…` and `aleo1…` strings for Aleo.
+#[derive(Deserialize, Serialize, Clone, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct SignatureOutput {
+    pub version: u8,
+    pub algorithm: SignatureAlgorithm,
+    pub signature: String,
+    pub public_key: String,
+}
+
+impl SignatureOutput {
+    pub fn new(algorithm: SignatureAlgorithm, signature: &[u8], public_key: &[u8]) -> Self {
+        Self {
+            version: SIGNATURE_OUTPUT_VERSION,
+            algorithm,
+            signature: hex::encode(signature),
+            public_key: hex::encode(public_key),
+        }
+    }
+}
+
+/// Public identifiers returned by `GetPublicIdentity`, `None` for the keys not asked for.
+#[derive(Deserialize, Serialize, Clone, Debug, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+pub struct PublicIdentity {
+    /// Ed25519 public key, in the requested encoding.
+    pub ed25519: Option<String>,
+    /// X25519 public key, in the requested encoding.
+    pub x25519: Option<String>,
+    /// The `aleo1…` address.
+    pub aleo_address: Option<String>,
+}
+
+/// The keys of an Aleo account returned by `DeriveViewKeyFromSeed`.
+#[derive(Deserialize, Serialize, Clone, Debug, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+pub struct AleoAccountKeys {
+    /// The `AViewKey1…` view key.
+    pub view_key: String,
+    /// The `aleo1…` address, if a domain separator was given.
+    pub address: Option<String>,
+}
+
+/// The display forms of an address or public key, see [`display`](crate::display).
+#[derive(Deserialize, Serialize, Clone, Debug, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+pub struct AddressDisplay {
+    /// The full string, in its one canonical encoding.
+    pub canonical: String,
+    /// The start and end of `canonical` around an ellipsis, for lists and headers.
+    pub short: String,
+}
+
+/// A record owned by the scanning account, as returned by `AleoScanRecords`.
+#[derive(Deserialize, Serialize, Clone, Debug)]
+pub struct DecryptedRecord {
+    /// Position of the ciphertext in the request.
+    pub index: usize,
+    /// The plaintext record in its canonical string form.
+    pub record: String,
+}
+
+/// Outcome of `ValidateMnemonic`.
+#[derive(Deserialize, Serialize, Clone, Debug, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+pub struct MnemonicValidation {
+    pub valid: bool,
+    /// The first word of the phrase missing from the wordlist.
+    pub invalid_word: Option<InvalidWord>,
+    /// Why the phrase is invalid, e.g. a wrong word count or checksum. `None` if it is valid.
+    pub reason: Option<String>,
+}
+
+/// A word of a mnemonic that isn't in the wordlist.
+#[derive(Deserialize, Serialize, Clone, Debug, PartialEq, Eq)]
+pub struct InvalidWord {
+    /// Position of the word in the phrase, from 0, or `None` if the word reported by the
+    /// wordlist can't be found in the normalized phrase.
+    pub index: Option<usize>,
+    pub word: String,
+}
+
+/// How the authorize procedures return the Aleo `Authorization`.
+#[derive(Deserialize, Serialize, Clone, Copy, Debug, Default, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum AuthorizationFormat {
+    /// The output of the runtime, unchanged.
+    #[default]
+    Raw,
+    /// The canonical snarkVM JSON string, ready to hand to a separate broadcaster.
+    Json,
+}
+
+/// An execution authorization with a fee authorized by another account, as returned by
+/// `AleoCombineAuthorizations`. See [`sponsor`](crate::sponsor).
+#[derive(Deserialize, Serialize, Clone, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct SponsoredAuthorization {
+    /// The execution authorization, in snarkVM's JSON form.
+    pub authorization: String,
+    /// The fee authorization, in snarkVM's JSON form.
+    pub fee_authorization: String,
+    /// The execution ID the fee pays for.
+    pub execution_id: String,
+    /// Address of the account paying the fee.
+    pub fee_payer: String,
+}
+
+/// Re-encodes an authorization returned by the runtime as snarkVM's canonical JSON string.
+pub(crate) fn authorization_to_json<N: Network>(raw: &[u8]) -> Result<Vec<u8>> {
+    let authorization = match Authorization::<N>::from_bytes_le(raw) {
+        Ok(authorization) => authorization,
+        // The runtime may already hand out the JSON form; parse it to validate and normalize.
+        Err(_) => serde_json::from_slice(raw)?,
+    };
+    Ok(authorization.to_string().into_bytes())
+}
+
+/// Parses a request signed by the runtime's `AleoSignRequest`.
+pub(crate) fn parse_request<N: Network>(raw: &[u8]) -> Result<Request<N>> {
+    match Request::<N>::from_bytes_le(raw) {
+        Ok(request) => Ok(request),
+        Err(_) => serde_json::from_slice(raw).map_err(Into::into),
+    }
+}
+
+/// Text encoding of the key returned by `GetPublicKeyString`.
+#[derive(Deserialize, Serialize, Clone, Copy, Debug, Default, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum KeyEncoding {
+    #[default]
+    Hex,
+    Base58,
+    Base64,
+}
+
+impl KeyEncoding {
+    pub fn encode(self, key: &[u8]) -> String {
+        use base64::Engine;
+
+        match self {
+            KeyEncoding::Hex => hex::encode(key),
+            KeyEncoding::Base58 => bs58::encode(key).into_string(),
+            KeyEncoding::Base64 => base64::engine::general_purpose::STANDARD.encode(key),
+        }
+    }
+}
+
+/// Encodes a procedure output the way composite procedures return it.
+pub(crate) fn to_bytes<T: Serialize>(output: &T) -> Result<Vec<u8>> {
+    serde_json::to_vec(output).map_err(Into::into)
+}
@@ -0,0 +1,50 @@
+// Copyright 2019-2023 Tauri Programme within The Commons Conservancy
+// SPDX-License-Identifier: Apache-2.0
+// SPDX-License-Identifier: MIT
+
+//! Pagination of the listing functions, whose results are always sorted so that pages stay
+//! stable between calls.
+
+use serde::{Deserialize, Serialize};
+
+/// Which part of a listing to return: `limit` entries starting at `offset`, or every entry
+/// from `offset` on without a limit.
+#[derive(Deserialize, Serialize, Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct PageRequest {
+    #[serde(default)]
+    pub offset: usize,
+    pub limit: Option<usize>,
+}
+
+impl PageRequest {
+    /// The whole listing.
+    pub const ALL: Self = Self {
+        offset: 0,
+        limit: None,
+    };
+
+    pub fn new(offset: usize, limit: usize) -> Self {
+        Self {
+            offset,
+            limit: Some(limit),
+        }
+    }
+}
+
+/// A page of a listing, with the number of entries in the whole listing.
+#[derive(Serialize, Clone, Debug, PartialEq, Eq)]
+pub struct Page<T> {
+    pub items: Vec<T>,
+    pub total: usize,
+}
+
+/// Cuts the page `request` out of the sorted `items`.
+pub(crate) fn paginate<T>(items: Vec<T>, request: PageRequest) -> Page<T> {
+    let total = items.len();
+    let items = items
+        .into_iter()
+        .skip(request.offset)
+        .take(request.limit.unwrap_or(usize::MAX))
+        .collect();
+    Page { items, total }
+}
@@ -0,0 +1,58 @@
+// Copyright 2019-2023 Tauri Programme within The Commons Conservancy
+// SPDX-License-Identifier: Apache-2.0
+// SPDX-License-Identifier: MIT
+
+//! The password taken by [`initialize`](crate::initialize) and friends.
+
+use std::ops::Deref;
+
+use zeroize::{Zeroize, Zeroizing};
+
+use crate::stronghold::{Error, Result};
+
+/// A password, wiped from memory when dropped.
+///
+/// Callers already holding the password in a [`Zeroizing`] buffer can hand it over as is, so
+/// it never exists as a plain `String`. Bytes must be UTF-8, see [`Password::try_from`].
+pub struct Password(Zeroizing<String>);
+
+impl Password {
+    pub fn as_str(&self) -> &str {
+        &self.0
+    }
+}
+
+impl Deref for Password {
+    type Target = str;
+
+    fn deref(&self) -> &str {
+        &self.0
+    }
+}
+
+impl From<String> for Password {
+    fn from(password: String) -> Self {
+        Self(Zeroizing::new(password))
+    }
+}
+
+impl From<Zeroizing<String>> for Password {
+    fn from(password: Zeroizing<String>) -> Self {
+        Self(password)
+    }
+}
+
+impl TryFrom<Zeroizing<Vec<u8>>> for Password {
+    type Error = Error;
+
+    /// Takes the buffer over without copying it, failing if it isn't UTF-8.
+    fn try_from(mut password: Zeroizing<Vec<u8>>) -> Result<Self> {
+        match String::from_utf8(std::mem::take(&mut *password)) {
+            Ok(password) => Ok(Self(Zeroizing::new(password))),
+            Err(e) => {
+                e.into_bytes().zeroize();
+                Err(Error::invalid_input("password", "is not valid UTF-8"))
+            }
+        }
+    }
+}
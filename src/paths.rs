@@ -0,0 +1,127 @@
+// Copyright 2019-2023 Tauri Programme within The Commons Conservancy
+// SPDX-License-Identifier: Apache-2.0
+// SPDX-License-Identifier: MIT
+
+//! Distinct types for the paths addressing a snapshot, a client, a vault and a record,
+//! so that passing them in the wrong order is a compile error.
+
+use std::path::{Path, PathBuf};
+
+use serde::{Deserialize, Serialize};
+
+use crate::BytesDto;
+
+/// Location of a snapshot file on disk.
+#[derive(Deserialize, Serialize, Clone, Debug, Hash, Eq, PartialEq, Ord, PartialOrd)]
+#[serde(transparent)]
+pub struct SnapshotPath(PathBuf);
+
+impl SnapshotPath {
+    pub fn as_path(&self) -> &Path {
+        &self.0
+    }
+
+    pub fn into_path_buf(self) -> PathBuf {
+        self.0
+    }
+}
+
+impl AsRef<Path> for SnapshotPath {
+    fn as_ref(&self) -> &Path {
+        &self.0
+    }
+}
+
+impl From<PathBuf> for SnapshotPath {
+    fn from(path: PathBuf) -> Self {
+        Self(path)
+    }
+}
+
+impl From<&Path> for SnapshotPath {
+    fn from(path: &Path) -> Self {
+        Self(path.to_path_buf())
+    }
+}
+
+impl From<String> for SnapshotPath {
+    fn from(path: String) -> Self {
+        Self(path.into())
+    }
+}
+
+impl From<&str> for SnapshotPath {
+    fn from(path: &str) -> Self {
+        Self(path.into())
+    }
+}
+
+impl From<SnapshotPath> for PathBuf {
+    fn from(path: SnapshotPath) -> Self {
+        path.0
+    }
+}
+
+macro_rules! bytes_path {
+    ($(#[$attr:meta])* $name:ident) => {
+        $(#[$attr])*
+        #[derive(Deserialize, Serialize, Clone, Hash, Eq, PartialEq, Ord, PartialOrd)]
+        #[serde(transparent)]
+        pub struct $name(BytesDto);
+
+        impl AsRef<[u8]> for $name {
+            fn as_ref(&self) -> &[u8] {
+                self.0.as_ref()
+            }
+        }
+
+        impl From<BytesDto> for $name {
+            fn from(path: BytesDto) -> Self {
+                Self(path)
+            }
+        }
+
+        impl From<String> for $name {
+            fn from(path: String) -> Self {
+                Self(BytesDto::Text(path))
+            }
+        }
+
+        impl From<&str> for $name {
+            fn from(path: &str) -> Self {
+                Self(BytesDto::Text(path.to_string()))
+            }
+        }
+
+        impl From<Vec<u8>> for $name {
+            fn from(path: Vec<u8>) -> Self {
+                Self(BytesDto::Raw(path))
+            }
+        }
+
+        impl From<$name> for BytesDto {
+            fn from(path: $name) -> Self {
+                path.0
+            }
+        }
+
+        impl From<$name> for Vec<u8> {
+            fn from(path: $name) -> Self {
+                path.0.into()
+            }
+        }
+    };
+}
+
+bytes_path!(
+    /// Path of a client inside a snapshot.
+    ClientPath
+);
+bytes_path!(
+    /// Path of a vault inside a client.
+    VaultPath
+);
+bytes_path!(
+    /// Path of a record inside a vault.
+    RecordPath
+);
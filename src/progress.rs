@@ -0,0 +1,24 @@
+// Copyright 2019-2023 Tauri Programme within The Commons Conservancy
+// SPDX-License-Identifier: Apache-2.0
+// SPDX-License-Identifier: MIT
+
+//! Coarse progress reporting for long running procedures.
+
+use serde::Serialize;
+
+/// Stage of an `AleoExecute` procedure, as reported by
+/// [`execute_procedure_with_progress`](crate::execute_procedure_with_progress).
+///
+/// The stages carry no data, so they can be forwarded to a frontend, e.g. as a Tauri event,
+/// without exposing keys or intermediate values.
+#[derive(Serialize, Clone, Copy, Debug, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+pub enum ExecutionStage {
+    /// Validating the procedure and loading the client.
+    Preparing,
+    /// Synthesizing the circuit, proving and building the transaction. The runtime does this
+    /// in a single step, so it is reported as one stage; it usually takes the longest.
+    Proving,
+    /// The transaction has been built and is being returned.
+    Done,
+}
@@ -0,0 +1,371 @@
+// Copyright 2019-2023 Tauri Programme within The Commons Conservancy
+// SPDX-License-Identifier: Apache-2.0
+// SPDX-License-Identifier: MIT
+
+//! Machine-readable description of the JSON accepted for each [`ProcedureDto`](crate::ProcedureDto),
+//! for tooling that validates requests before sending them.
+//!
+//! A procedure is sent as `{ "type": <name>, "payload": { <fields> } }`. Field names are given
+//! exactly as they appear in the JSON, including the renamed ones such as `privateKey` and
+//! `sizeBytes`. Types are named as follows:
+//!
+//! - `Location`: `{ "type": "Generic", "payload": { "vault", "record" } }` or
+//!   `{ "type": "Counter", "payload": { "vault", "counter" } }`, paths as strings or byte arrays.
+//! - `Bytes`: a UTF-8 string or an array of byte values.
+//! - `Slip10DeriveInput`: `{ "type": "Seed" | "Key", "payload": Location }`.
+//! - `Ed25519PublicKey`: `{ "type": "Bytes", "payload": number[] }` or
+//!   `{ "type": "Location", "payload": Location }`.
+//! - `Transition`: `{ "program_id", "function_name", "inputs", "input_types", "is_root" }`,
+//!   with `is_root` a boolean that defaults to `false`.
+//! - `FeeSource`: `{ "type": "Public" }` or `{ "type": "Private", "payload": Record }`.
+//! - Aleo types (`Identifier`, `ProgramID`, `Field`, `Value`, `ValueType`, `Record`) in their
+//!   snarkVM string form.
+//! - `T[]` for lists and `[A, B]` for pairs.
+
+use serde::Serialize;
+
+/// A field of a procedure payload.
+#[derive(Serialize, Clone, Copy, Debug, PartialEq, Eq)]
+pub struct FieldSchema {
+    pub name: &'static str,
+    #[serde(rename = "type")]
+    pub ty: &'static str,
+    /// Whether the field may be left out or set to `null`.
+    pub optional: bool,
+}
+
+/// The payload fields of a procedure.
+#[derive(Serialize, Clone, Copy, Debug, PartialEq, Eq)]
+pub struct ProcedureSchema {
+    pub name: &'static str,
+    pub fields: &'static [FieldSchema],
+}
+
+const fn required(name: &'static str, ty: &'static str) -> FieldSchema {
+    FieldSchema {
+        name,
+        ty,
+        optional: false,
+    }
+}
+
+const fn optional(name: &'static str, ty: &'static str) -> FieldSchema {
+    FieldSchema {
+        name,
+        ty,
+        optional: true,
+    }
+}
+
+const fn procedure(name: &'static str, fields: &'static [FieldSchema]) -> ProcedureSchema {
+    ProcedureSchema { name, fields }
+}
+
+/// Schemas of every procedure, in the order of [`PROCEDURE_NAMES`](crate::PROCEDURE_NAMES).
+///
+/// Kept by hand next to [`ProcedureDto`](crate::ProcedureDto): update both together.
+pub const PROCEDURE_SCHEMAS: &[ProcedureSchema] = &[
+    procedure(
+        "SLIP10Generate",
+        &[
+            required("output", "Location"),
+            optional("sizeBytes", "number"),
+        ],
+    ),
+    procedure(
+        "SLIP10Derive",
+        &[
+            required("curve", "Curve"),
+            required("chain", "number[]"),
+            required("input", "Slip10DeriveInput"),
+            required("output", "Location"),
+            required("network", "string"),
+        ],
+    ),
+    procedure(
+        "SLIP10DerivePath",
+        &[
+            required("curve", "Curve"),
+            required("path", "string"),
+            required("input", "Slip10DeriveInput"),
+            required("output", "Location"),
+            required("network", "string"),
+        ],
+    ),
+    procedure(
+        "SLIP10Rotate",
+        &[
+            required("curve", "Curve"),
+            required("chain", "number[]"),
+            required("input", "Slip10DeriveInput"),
+            required("output", "Location"),
+            required("network", "string"),
+        ],
+    ),
+    procedure(
+        "SLIP10DeriveString",
+        &[
+            required("curve", "Curve"),
+            required("path", "string"),
+            required("seed", "Location"),
+            required("network", "string"),
+            required("type", "KeyType"),
+            optional("encoding", "KeyEncoding"),
+        ],
+    ),
+    procedure(
+        "DeriveViewKeyFromSeed",
+        &[
+            required("curve", "Curve"),
+            required("seed", "Location"),
+            required("index", "number"),
+            required("network", "string"),
+            optional("ext", "Identifier"),
+        ],
+    ),
+    procedure(
+        "BIP39Recover",
+        &[
+            required("mnemonic", "string"),
+            optional("passphrase", "string"),
+            required("output", "Location"),
+            optional("wordlist", "string[]"),
+        ],
+    ),
+    procedure(
+        "BIP39Generate",
+        &[
+            optional("passphrase", "string"),
+            required("output", "Location"),
+            optional("wordlist", "string[]"),
+        ],
+    ),
+    procedure(
+        "RotateBIP39Passphrase",
+        &[
+            required("mnemonic", "string"),
+            required("seed", "Location"),
+            optional("oldPassphrase", "string"),
+            optional("newPassphrase", "string"),
+            required("output", "Location"),
+        ],
+    ),
+    #[cfg(feature = "unsafe-export")]
+    procedure(
+        "ConvertMnemonicToSeed",
+        &[
+            required("mnemonic", "string"),
+            optional("passphrase", "string"),
+        ],
+    ),
+    procedure(
+        "ValidateMnemonic",
+        &[
+            required("mnemonic", "string"),
+            optional("language", "string"),
+        ],
+    ),
+    procedure("Random", &[required("sizeBytes", "number")]),
+    procedure(
+        "GenerateKey",
+        &[
+            required("type", "KeyType"),
+            required("output", "Location"),
+            optional("returnPublicKey", "boolean"),
+        ],
+    ),
+    procedure(
+        "GenerateEd25519AndRegister",
+        &[required("output", "Location")],
+    ),
+    procedure(
+        "ShamirSplit",
+        &[
+            required("seed", "Location"),
+            required("threshold", "number"),
+            required("shares", "number"),
+        ],
+    ),
+    procedure(
+        "ShamirCombine",
+        &[
+            required("shares", "string[]"),
+            required("output", "Location"),
+        ],
+    ),
+    procedure(
+        "ImportAleoPrivateKey",
+        &[required("key", "string"), required("output", "Location")],
+    ),
+    procedure(
+        "PublicKey",
+        &[
+            required("type", "KeyType"),
+            required("privateKey", "Location"),
+        ],
+    ),
+    procedure(
+        "GetPublicKeyString",
+        &[
+            required("type", "KeyType"),
+            required("privateKey", "Location"),
+            optional("encoding", "KeyEncoding"),
+        ],
+    ),
+    procedure(
+        "GetPublicIdentity",
+        &[
+            optional("ed25519", "Location"),
+            optional("x25519", "Location"),
+            optional("aleo", "[Location, Identifier]"),
+            optional("encoding", "KeyEncoding"),
+        ],
+    ),
+    procedure(
+        "Ed25519Sign",
+        &[
+            required("privateKey", "Location"),
+            required("msg", "string"),
+        ],
+    ),
+    procedure(
+        "AleoSign",
+        &[
+            required("privateKey", "Location"),
+            required("msg", "string"),
+            required("ext", "Identifier"),
+        ],
+    ),
+    procedure(
+        "Ed25519SignStructured",
+        &[
+            required("privateKey", "Location"),
+            required("msg", "string"),
+        ],
+    ),
+    procedure(
+        "VerifyEd25519",
+        &[
+            required("publicKey", "Ed25519PublicKey"),
+            required("msg", "Bytes"),
+            required("signature", "number[]"),
+        ],
+    ),
+    procedure(
+        "AleoSignStructured",
+        &[
+            required("privateKey", "Location"),
+            required("msg", "string"),
+            required("ext", "Identifier"),
+        ],
+    ),
+    procedure(
+        "SignMany",
+        &[
+            required("algorithm", "SignatureAlgorithm"),
+            required("privateKey", "Location"),
+            required("messages", "Bytes[]"),
+            optional("ext", "Identifier"),
+        ],
+    ),
+    procedure(
+        "GetAleoAddress",
+        &[
+            required("privateKey", "Location"),
+            required("ext", "Identifier"),
+        ],
+    ),
+    procedure(
+        "GetAleoViewKey",
+        &[
+            required("privateKey", "Location"),
+            // A `PhantomData` marker, which must be sent as `null`.
+            required("_network", "null"),
+        ],
+    ),
+    procedure(
+        "AleoScanRecords",
+        &[
+            required("privateKey", "Location"),
+            required("ciphertexts", "Record[]"),
+        ],
+    ),
+    procedure(
+        "AleoSignRequest",
+        &[
+            required("program_id", "ProgramID"),
+            required("function_name", "Identifier"),
+            required("inputs", "Value[]"),
+            required("input_types", "ValueType[]"),
+            optional("root_tvk", "Field"),
+            required("is_root", "boolean"),
+            required("privateKey", "Location"),
+        ],
+    ),
+    procedure(
+        "AleoSignRequests",
+        &[
+            required("transitions", "Transition[]"),
+            required("privateKey", "Location"),
+        ],
+    ),
+    procedure(
+        "AleoAuthorize",
+        &[
+            required("private_key", "Location"),
+            required("program_id", "ProgramID"),
+            required("function_name", "Identifier"),
+            required("inputs", "Value[]"),
+            optional("input_types", "ValueType[]"),
+            optional("format", "AuthorizationFormat"),
+        ],
+    ),
+    procedure(
+        "AleoAuthorizeFeePublic",
+        &[
+            required("private_key", "Location"),
+            required("base_fee_in_microcredits", "number"),
+            required("priority_fee_in_microcredits", "number"),
+            required("deployment_or_execution_id", "Field"),
+            optional("format", "AuthorizationFormat"),
+        ],
+    ),
+    procedure(
+        "AleoAuthorizeFeePrivate",
+        &[
+            required("private_key", "Location"),
+            required("credits", "Record"),
+            required("base_fee_in_microcredits", "number"),
+            required("priority_fee_in_microcredits", "number"),
+            required("deployment_or_execution_id", "Field"),
+            optional("format", "AuthorizationFormat"),
+        ],
+    ),
+    procedure(
+        "AleoCombineAuthorizations",
+        &[
+            required("authorization", "string"),
+            required("fee_authorization", "string"),
+        ],
+    ),
+    procedure(
+        "AleoExecute",
+        &[
+            required("privateKey", "Location"),
+            required("program_id", "ProgramID"),
+            required("function_name", "Identifier"),
+            required("inputs", "Value[]"),
+            required("fee", "FeeSource"),
+            required("priority_fee_in_microcredits", "number"),
+            optional("base_fee_in_microcredits", "number"),
+            required("base_url", "string"),
+            optional("input_types", "ValueType[]"),
+            optional("idempotency_key", "string"),
+        ],
+    ),
+];
+
+/// The schema of the procedure `name`, if it exists.
+pub fn procedure_schema(name: &str) -> Option<&'static ProcedureSchema> {
+    PROCEDURE_SCHEMAS.iter().find(|schema| schema.name == name)
+}
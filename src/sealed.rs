@@ -0,0 +1,72 @@
+// Copyright 2019-2023 Tauri Programme within The Commons Conservancy
+// SPDX-License-Identifier: Apache-2.0
+// SPDX-License-Identifier: MIT
+
+//! Store values encrypted under a vault-held key on top of the snapshot encryption, written by
+//! [`save_store_record_sealed`](crate::save_store_record_sealed).
+//!
+//! A sealed value is `[SEALED_VERSION] || nonce || tag || ciphertext`, encrypted with
+//! XChaCha20-Poly1305 inside the runtime. The store key is the associated data, so a sealed
+//! value can't be moved to another key unnoticed. The sealing key must be a 32 byte secret,
+//! e.g. created with [`generate_secret`](crate::generate_secret).
+
+use iota_stronghold::{
+    procedures::{AeadCipher, AeadDecrypt, AeadEncrypt},
+    Client, Location,
+};
+
+use crate::stronghold::{Error, Result};
+
+/// Current layout of a sealed value. Bumped whenever it changes.
+pub const SEALED_VERSION: u8 = 1;
+
+const NONCE_LENGTH: usize = 24;
+const TAG_LENGTH: usize = 16;
+
+pub(crate) fn seal(
+    client: &Client,
+    sealing_key: Location,
+    store_key: &[u8],
+    plaintext: Vec<u8>,
+) -> Result<Vec<u8>> {
+    let mut nonce = vec![0u8; NONCE_LENGTH];
+    crypto::utils::rand::fill(&mut nonce)?;
+    // Returned as the tag followed by the ciphertext.
+    let encrypted = client.execute_procedure(AeadEncrypt {
+        cipher: AeadCipher::XChaCha20Poly1305,
+        associated_data: store_key.to_vec(),
+        plaintext,
+        nonce: nonce.clone(),
+        key: sealing_key,
+    })?;
+    let mut sealed = Vec::with_capacity(1 + NONCE_LENGTH + encrypted.len());
+    sealed.push(SEALED_VERSION);
+    sealed.extend_from_slice(&nonce);
+    sealed.extend_from_slice(&encrypted);
+    Ok(sealed)
+}
+
+pub(crate) fn unseal(
+    client: &Client,
+    sealing_key: Location,
+    store_key: &[u8],
+    sealed: &[u8],
+) -> Result<Vec<u8>> {
+    let invalid = || Error::invalid_input("sealed store value", "not written by the sealed API");
+    let (&version, rest) = sealed.split_first().ok_or_else(invalid)?;
+    if version != SEALED_VERSION || rest.len() < NONCE_LENGTH + TAG_LENGTH {
+        return Err(invalid());
+    }
+    let (nonce, rest) = rest.split_at(NONCE_LENGTH);
+    let (tag, ciphertext) = rest.split_at(TAG_LENGTH);
+    client
+        .execute_procedure(AeadDecrypt {
+            cipher: AeadCipher::XChaCha20Poly1305,
+            associated_data: store_key.to_vec(),
+            ciphertext: ciphertext.to_vec(),
+            tag: tag.to_vec(),
+            nonce: nonce.to_vec(),
+            key: sealing_key,
+        })
+        .map_err(Into::into)
+}
@@ -0,0 +1,126 @@
+// Copyright 2019-2023 Tauri Programme within The Commons Conservancy
+// SPDX-License-Identifier: Apache-2.0
+// SPDX-License-Identifier: MIT
+
+//! Shamir secret sharing over GF(2^8), used by the `ShamirSplit` and `ShamirCombine`
+//! procedures to back a seed up as several shares.
+//!
+//! Every byte of the secret is shared with its own random polynomial of degree
+//! `threshold - 1`, in the field of the AES polynomial `x^8 + x^4 + x^3 + x + 1`. A share is
+//! `[threshold, x, y_0, y_1, ...]`, with `x` in `1..=255` and one `y` per secret byte.
+//! Field arithmetic doesn't branch on secret data.
+
+use zeroize::Zeroizing;
+
+use crate::stronghold::{Error, Result};
+
+/// Splits `secret` into `shares` shares, any `threshold` of which rebuild it.
+pub fn split(secret: &[u8], threshold: u8, shares: u8) -> Result<Vec<Zeroizing<Vec<u8>>>> {
+    if !(2..=shares).contains(&threshold) {
+        return Err(Error::invalid_input(
+            "threshold",
+            format!("must be between 2 and the number of shares ({shares})"),
+        ));
+    }
+    if secret.is_empty() {
+        return Err(Error::invalid_input("secret", "must not be empty"));
+    }
+
+    let mut output: Vec<_> = (1..=shares)
+        .map(|x| {
+            let mut share = Zeroizing::new(Vec::with_capacity(2 + secret.len()));
+            share.extend_from_slice(&[threshold, x]);
+            share
+        })
+        .collect();
+    let mut coefficients = Zeroizing::new(vec![0u8; usize::from(threshold)]);
+    for &byte in secret {
+        coefficients[0] = byte;
+        crypto::utils::rand::fill(&mut coefficients[1..])?;
+        for share in output.iter_mut() {
+            let x = share[1];
+            // Horner's rule, from the highest coefficient down.
+            let y = coefficients
+                .iter()
+                .rev()
+                .fold(0, |acc, &coefficient| mul(acc, x) ^ coefficient);
+            share.push(y);
+        }
+    }
+    Ok(output)
+}
+
+/// Rebuilds the secret from at least `threshold` shares of the same [`split`].
+pub fn combine(shares: &[Zeroizing<Vec<u8>>]) -> Result<Zeroizing<Vec<u8>>> {
+    let invalid = |detail: &str| Error::invalid_input("shares", detail);
+    let first = shares.first().ok_or_else(|| invalid("none given"))?;
+    if first.len() < 3 {
+        return Err(invalid("a share is too short"));
+    }
+    let threshold = first[0];
+    if shares.len() < usize::from(threshold) {
+        return Err(invalid(&format!(
+            "{threshold} are needed, got {}",
+            shares.len()
+        )));
+    }
+    for (i, share) in shares.iter().enumerate() {
+        if share.len() != first.len() || share[0] != threshold {
+            return Err(invalid("they come from different splits"));
+        }
+        if share[1] == 0 || shares[..i].iter().any(|other| other[1] == share[1]) {
+            return Err(invalid("a share is repeated or malformed"));
+        }
+    }
+
+    // Lagrange interpolation at x = 0 over the first `threshold` shares.
+    let shares = &shares[..usize::from(threshold)];
+    let basis: Zeroizing<Vec<u8>> = Zeroizing::new(
+        shares
+            .iter()
+            .map(|share| {
+                shares
+                    .iter()
+                    .filter(|other| other[1] != share[1])
+                    .fold(1, |acc, other| mul(acc, div(other[1], other[1] ^ share[1])))
+            })
+            .collect(),
+    );
+    let mut secret = Zeroizing::new(Vec::with_capacity(first.len() - 2));
+    for position in 2..first.len() {
+        let byte = shares
+            .iter()
+            .zip(basis.iter())
+            .fold(0, |acc, (share, &weight)| {
+                acc ^ mul(share[position], weight)
+            });
+        secret.push(byte);
+    }
+    Ok(secret)
+}
+
+fn mul(mut a: u8, mut b: u8) -> u8 {
+    let mut product = 0;
+    for _ in 0..8 {
+        product ^= (b & 1).wrapping_neg() & a;
+        let carry = (a >> 7).wrapping_neg();
+        a = (a << 1) ^ (carry & 0x1b);
+        b >>= 1;
+    }
+    product
+}
+
+/// `a / b` for a non-zero `b`, as `a * b^254`.
+fn div(a: u8, b: u8) -> u8 {
+    let mut inverse = 1;
+    let mut power = b;
+    let mut exponent = 254u8;
+    while exponent > 0 {
+        if exponent & 1 == 1 {
+            inverse = mul(inverse, power);
+        }
+        power = mul(power, power);
+        exponent >>= 1;
+    }
+    mul(a, inverse)
+}
@@ -0,0 +1,52 @@
+// Copyright 2019-2023 Tauri Programme within The Commons Conservancy
+// SPDX-License-Identifier: Apache-2.0
+// SPDX-License-Identifier: MIT
+
+//! Persistence of snapshots somewhere else than a local file, e.g. a remote key-value store
+//! or a blob sealed by a platform enclave. See [`Builder::snapshot_store`](crate::Builder::snapshot_store).
+
+use std::{io, path::PathBuf};
+
+/// Keeps encrypted snapshots as opaque blobs, keyed by their snapshot path.
+///
+/// Snapshots are encrypted before they reach the store, which never sees a secret. A
+/// [`write`](Self::write) must replace the previous blob atomically, since a torn write loses
+/// the snapshot.
+pub trait SnapshotStore: Send + Sync {
+    /// The blob stored under `key`, failing with [`io::ErrorKind::NotFound`] if there is none.
+    fn read(&self, key: &str) -> io::Result<Vec<u8>>;
+
+    /// Stores `bytes` under `key`, replacing the previous blob.
+    fn write(&self, key: &str, bytes: &[u8]) -> io::Result<()>;
+
+    /// Removes the blob stored under `key`. Removing a missing blob is not an error.
+    fn delete(&self, key: &str) -> io::Result<()>;
+}
+
+/// Stores every snapshot in the file named by its key.
+///
+/// This is what the default [`Backend::File`](crate::stronghold::Backend::File) does, minus
+/// the checksum and file permissions it maintains, and a starting point for stores that
+/// build on the filesystem, e.g. to mirror snapshots elsewhere.
+#[derive(Clone, Copy, Debug, Default)]
+pub struct FileStore;
+
+impl SnapshotStore for FileStore {
+    fn read(&self, key: &str) -> io::Result<Vec<u8>> {
+        std::fs::read(key)
+    }
+
+    fn write(&self, key: &str, bytes: &[u8]) -> io::Result<()> {
+        let mut staged = PathBuf::from(key).into_os_string();
+        staged.push(".staged");
+        std::fs::write(&staged, bytes)?;
+        std::fs::rename(&staged, key)
+    }
+
+    fn delete(&self, key: &str) -> io::Result<()> {
+        match std::fs::remove_file(key) {
+            Err(e) if e.kind() == io::ErrorKind::NotFound => Ok(()),
+            result => result,
+        }
+    }
+}
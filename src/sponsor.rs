@@ -0,0 +1,89 @@
+// Copyright 2019-2023 Tauri Programme within The Commons Conservancy
+// SPDX-License-Identifier: Apache-2.0
+// SPDX-License-Identifier: MIT
+
+//! Sponsored fees, paid by another account than the one executing, e.g. a relayer offering
+//! gasless transactions.
+//!
+//! The flow is:
+//!
+//! 1. the user authorizes the execution with `AleoAuthorize` in the JSON format;
+//! 2. the sponsor authorizes a `fee_public` or `fee_private` for the execution ID of that
+//!    authorization, with its own key and wherever it keeps it;
+//! 3. `AleoCombineAuthorizations` checks that the fee pays for this very execution and returns
+//!    both in one [`SponsoredAuthorization`], for a prover to turn into a transaction.
+//!
+//! The execution ID covers the randomness of the authorization, so a fee can't be
+//! authorized before the execution it pays for.
+
+use std::str::FromStr;
+
+use snarkvm_console::{
+    network::Network,
+    program::{Literal, Plaintext, Request, Value},
+};
+use snarkvm_synthesizer::process::Authorization;
+
+use crate::{
+    output::SponsoredAuthorization,
+    stronghold::{Error, Result},
+};
+
+const FEE_FUNCTIONS: &[&str] = &["fee_public", "fee_private"];
+
+/// Checks that `fee_authorization` pays for the execution authorized by `authorization`, both
+/// in snarkVM's JSON form.
+pub fn combine_authorizations<N: Network>(
+    authorization: &str,
+    fee_authorization: &str,
+) -> Result<SponsoredAuthorization> {
+    let parse = |field: &'static str, json: &str| {
+        Authorization::<N>::from_str(json).map_err(|e| Error::invalid_value(field, e.to_string()))
+    };
+    let execution = parse("authorization", authorization)?;
+    let fee = parse("fee_authorization", fee_authorization)?;
+
+    if execution.to_vec_deque().iter().any(is_fee) {
+        return Err(Error::invalid_input(
+            "authorization",
+            "must authorize an execution, not a fee",
+        ));
+    }
+    let execution_id = execution
+        .to_execution_id()
+        .map_err(|e| Error::invalid_value("authorization", e.to_string()))?;
+
+    let requests = fee.to_vec_deque();
+    let request = match (requests.len(), requests.front()) {
+        (1, Some(request)) if is_fee(request) => request,
+        _ => {
+            return Err(Error::invalid_input(
+                "fee_authorization",
+                "must authorize a single `credits.aleo` fee_public or fee_private",
+            ))
+        }
+    };
+    // The execution ID is the last input of both fee functions.
+    match request.inputs().last() {
+        Some(Value::Plaintext(Plaintext::Literal(Literal::Field(id), _)))
+            if *id == execution_id => {}
+        _ => {
+            return Err(Error::invalid_input(
+                "fee_authorization",
+                format!("does not pay for execution `{execution_id}`"),
+            ))
+        }
+    }
+
+    Ok(SponsoredAuthorization {
+        authorization: execution.to_string(),
+        fee_authorization: fee.to_string(),
+        execution_id: execution_id.to_string(),
+        fee_payer: request.signer().to_string(),
+    })
+}
+
+fn is_fee<N: Network>(request: &Request<N>) -> bool {
+    request.program_id().to_string() == "credits.aleo"
+        && FEE_FUNCTIONS.contains(&request.function_name().to_string().as_str())
+}
@@ -0,0 +1,154 @@
+// Copyright 2019-2023 Tauri Programme within The Commons Conservancy
+// SPDX-License-Identifier: Apache-2.0
+// SPDX-License-Identifier: MIT
+
+use std::fmt;
+
+use serde::{Deserialize, Serialize};
+
+use crate::stronghold::{Error, Result};
+
+/// The bytes a store key is stored under: the UTF-8 encoding of the string as is, without
+/// Unicode normalization, terminator or length prefix. Tools reading the snapshot directly
+/// must use the same bytes to find a record.
+pub fn encode_key(key: &str) -> &[u8] {
+    key.as_bytes()
+}
+
+/// Decodes the raw bytes of a store key, failing if they aren't UTF-8 and so weren't written
+/// through this plugin.
+pub fn decode_key(key: Vec<u8>) -> Result<String> {
+    String::from_utf8(key).map_err(|e| {
+        Error::invalid_input(
+            "store key",
+            format!("{} is not valid UTF-8", hex::encode(e.as_bytes())),
+        )
+    })
+}
+
+/// The key that `key` is stored under in `namespace`, the [`StoreKey`] encoding, or `key`
+/// itself without a namespace.
+///
+/// Un-namespaced keys shaped like an encoded [`StoreKey`] would still alias a namespaced key,
+/// so features sharing a client should all use a namespace.
+pub fn scoped_key(namespace: Option<&str>, key: String) -> String {
+    match namespace {
+        Some(namespace) => StoreKey::new(namespace, key).to_string(),
+        None => key,
+    }
+}
+
+/// A store key scoped to a namespace.
+///
+/// It is stored as `<namespace length>:<namespace>:<id>`. The length prefix keeps keys from
+/// different namespaces apart even when the namespace or id contain `:` themselves.
+///
+/// The `_namespaced` store functions take it directly, e.g.
+/// [`get_store_record_namespaced`](crate::get_store_record_namespaced); the others take its
+/// encoding, from [`ToString`], as their key.
+#[derive(Deserialize, Serialize, Clone, Debug, Hash, Eq, PartialEq, Ord, PartialOrd)]
+pub struct StoreKey {
+    pub namespace: String,
+    pub id: String,
+}
+
+impl StoreKey {
+    pub fn new(namespace: impl Into<String>, id: impl Into<String>) -> Self {
+        Self {
+            namespace: namespace.into(),
+            id: id.into(),
+        }
+    }
+
+    /// The prefix shared by the encoding of every key in `namespace`.
+    pub fn namespace_prefix(namespace: &str) -> String {
+        format!("{}:{namespace}:", namespace.len())
+    }
+
+    /// Decodes a raw store key, failing if it wasn't produced by a [`StoreKey`].
+    pub fn parse(key: &str) -> Result<Self> {
+        let invalid =
+            || Error::invalid_input("store key", format!("`{key}` is not a namespaced key"));
+        let (len, rest) = key.split_once(':').ok_or_else(invalid)?;
+        let len: usize = len.parse().map_err(|_| invalid())?;
+        let namespace = rest.get(..len).ok_or_else(invalid)?;
+        let id = rest
+            .get(len..)
+            .and_then(|rest| rest.strip_prefix(':'))
+            .ok_or_else(invalid)?;
+        Ok(Self::new(namespace, id))
+    }
+}
+
+impl fmt::Display for StoreKey {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}{}", Self::namespace_prefix(&self.namespace), self.id)
+    }
+}
+
+impl From<StoreKey> for String {
+    fn from(key: StoreKey) -> Self {
+        key.to_string()
+    }
+}
+
+impl TryFrom<&str> for StoreKey {
+    type Error = Error;
+
+    fn try_from(key: &str) -> Result<Self> {
+        Self::parse(key)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn round_trip(namespace: &str, id: &str) {
+        let key = StoreKey::new(namespace, id);
+        assert_eq!(StoreKey::parse(&key.to_string()).unwrap(), key);
+    }
+
+    #[test]
+    fn parse_round_trips() {
+        round_trip("settings", "theme");
+        round_trip("", "");
+        round_trip("settings", "");
+    }
+
+    #[test]
+    fn separators_in_namespace_and_id() {
+        round_trip("a:b", "c");
+        round_trip("a", "b:c");
+        round_trip(":", ":");
+        assert_ne!(
+            StoreKey::new("a:b", "c").to_string(),
+            StoreKey::new("a", "b:c").to_string()
+        );
+    }
+
+    #[test]
+    fn length_prefix_counts_bytes() {
+        let key = StoreKey::new("réglages", "thème");
+        assert_eq!(key.to_string(), "9:réglages:thème");
+        round_trip("réglages", "thème");
+        round_trip("🔑", "id");
+        // A length counted in characters doesn't match the encoding.
+        assert!(StoreKey::parse("8:réglages:thème").is_err());
+    }
+
+    #[test]
+    fn rejects_keys_not_produced_by_store_key() {
+        for key in ["theme", "x:settings:theme", "20:settings:theme", "3:abcd"] {
+            assert!(StoreKey::parse(key).is_err(), "{key}");
+        }
+    }
+
+    #[test]
+    fn un_namespaced_keys_can_alias_namespaced_ones() {
+        let namespaced = scoped_key(Some("settings"), "theme".into());
+        let plain = scoped_key(None, namespaced.clone());
+        assert_eq!(plain, namespaced);
+        assert_eq!(scoped_key(None, "theme".into()), "theme");
+    }
+}
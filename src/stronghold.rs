@@ -2,23 +2,434 @@
 // SPDX-License-Identifier: Apache-2.0
 // SPDX-License-Identifier: MIT
 
-use std::{convert::TryFrom, ops::Deref, path::Path};
+use std::{
+    collections::{BTreeSet, HashMap},
+    convert::TryFrom,
+    fmt,
+    io::Read,
+    ops::Deref,
+    path::{Path, PathBuf},
+    sync::{
+        atomic::{AtomicU64, Ordering},
+        Arc, Mutex,
+    },
+    time::{Duration, SystemTime, UNIX_EPOCH},
+};
 
-use iota_stronghold::{KeyProvider, SnapshotPath};
+use crypto::{hashes::sha::SHA256, macs::hmac::HMAC_SHA256};
+use iota_stronghold::{Client, KeyProvider, SnapshotPath};
 use serde::{Serialize, Serializer};
 
+use crate::snapshot_store::SnapshotStore;
+
 pub type Result<T> = std::result::Result<T, Error>;
 
-#[derive(Debug, thiserror::Error)]
+/// Client holding the plugin's own metadata, next to the application's clients.
+pub const METADATA_CLIENT: &[u8] = b"__tauri_plugin_stronghold_metadata";
+
+/// Magic bytes every stronghold snapshot file starts with.
+const SNAPSHOT_MAGIC: [u8; 5] = *b"PARTI";
+
+/// Snapshot format version, as `[major, minor]`, read and written by the bundled `iota_stronghold`.
+pub const SNAPSHOT_FORMAT_VERSION: [u8; 2] = [3, 0];
+
+const PASSWORD_VERIFIER_KEY: &[u8] = b"password_verifier";
+
+/// Prefix of the metadata store keys holding the record paths written to a vault.
+const RECORD_INDEX_PREFIX: &[u8] = b"record_index:";
+
+/// Prefix of the metadata store keys holding when a store record expires.
+const STORE_EXPIRY_PREFIX: &[u8] = b"store_expiry:";
+
+/// Prefix of the metadata store keys holding the counter of a vault.
+const COUNTER_PREFIX: &[u8] = b"counter:";
+const VERIFIER_SALT_LENGTH: usize = 32;
+
+/// Appended to the snapshot path to name the file holding its checksum.
+const CHECKSUM_SUFFIX: &str = ".checksum";
+/// Appended to the checksum path to name the checksum of a save being written.
+const CHECKSUM_STAGED_SUFFIX: &str = ".staged";
+/// First byte of a keyed checksum. Checksums of older releases weren't keyed and have none.
+const CHECKSUM_FORMAT: u8 = 2;
+const CHECKSUM_SALT_LENGTH: usize = 32;
+/// Separates the key of the checksum from the snapshot key it is derived from.
+const CHECKSUM_KEY_LABEL: &[u8] = b"tauri-plugin-stronghold snapshot checksum";
+
+/// How often [`Stronghold::save`] tries to write the snapshot before giving up on a
+/// transient I/O error.
+pub const SAVE_ATTEMPTS: u32 = 3;
+
+/// Wait before the first retry of a save, doubled for every further retry.
+const SAVE_RETRY_BACKOFF: Duration = Duration::from_millis(50);
+
+#[derive(thiserror::Error)]
 pub enum Error {
     #[error("stronghold not initialized")]
     StrongholdNotInitialized,
+    #[error("stronghold already initialized")]
+    AlreadyInitialized,
+    #[error("a secret is already stored at this location")]
+    RecordExists,
+    #[error("too many failed attempts, retry in {} seconds", retry_after.as_secs().max(1))]
+    TooManyAttempts { retry_after: Duration },
+    #[error("no legacy hash function registered for this snapshot")]
+    LegacyHashFunctionMissing,
+    #[error("password and confirmation do not match")]
+    PasswordMismatch,
+    #[error("snapshot has no password verifier")]
+    PasswordVerifierMissing,
+    #[error("snapshot file does not exist")]
+    SnapshotNotFound,
+    #[error("no store record with this key")]
+    StoreRecordNotFound,
+    #[error("the store record does not hold the expected value")]
+    Conflict,
+    #[error("file is not a stronghold snapshot")]
+    NotASnapshot,
+    #[error("snapshot file is corrupted")]
+    Corrupted,
+    #[error(
+        "snapshot format version {}.{} is not supported (expected {}.{}); \
+         open and save it with the plugin release that created it to upgrade",
+        found[0], found[1], expected[0], expected[1]
+    )]
+    IncompatibleSnapshotVersion { found: [u8; 2], expected: [u8; 2] },
+    #[error("failed to {operation} {}: {source}", path.display())]
+    Snapshot {
+        operation: &'static str,
+        path: PathBuf,
+        #[source]
+        source: Box<Error>,
+    },
+    /// The runtime failed while running `procedure`, or one of its steps for composite
+    /// procedures.
+    #[error("`{procedure}` failed: {source}")]
+    ProcedureFailed {
+        procedure: &'static str,
+        #[source]
+        source: Box<Error>,
+    },
+    #[error("procedure {index} failed: {source}")]
+    BatchProcedure {
+        index: usize,
+        #[source]
+        source: Box<Error>,
+    },
+    #[error("invalid {field}: {detail}")]
+    InvalidInput { field: String, detail: String },
+    /// Like [`Error::InvalidInput`], for a value that failed to parse. The message of the
+    /// parser may quote the value, e.g. a private record, so it is left out of the display.
+    #[error("invalid {field}: the value is malformed")]
+    InvalidValue { field: String, detail: Redacted },
+    /// An input or record over the [`InputLimits`](crate::inputs::InputLimits) of the
+    /// collection.
+    #[error("{field} has {size} {unit}, the limit is {limit}")]
+    InputTooLarge {
+        field: String,
+        size: usize,
+        unit: &'static str,
+        limit: usize,
+    },
+    #[error("expected {expected} inputs, got {got}")]
+    InputCountMismatch { expected: usize, got: usize },
+    #[error("input {index} should be a {expected}, got a {got}")]
+    InputTypeMismatch {
+        index: usize,
+        expected: String,
+        got: String,
+    },
     #[error(transparent)]
     Stronghold(#[from] iota_stronghold::ClientError),
     #[error(transparent)]
     Memory(#[from] iota_stronghold::MemoryError),
     #[error(transparent)]
     Procedure(#[from] iota_stronghold::procedures::ProcedureError),
+    #[error(transparent)]
+    Crypto(#[from] crypto::Error),
+    #[error(transparent)]
+    Io(#[from] std::io::Error),
+    #[error("I/O error persisted after {SAVE_ATTEMPTS} attempts, retrying later may succeed: {0}")]
+    IoTransient(std::io::Error),
+    #[error("I/O error: {0}")]
+    IoPermanent(std::io::Error),
+    /// Displayed without the message of `serde_json`, which may quote the offending value.
+    #[error(
+        "malformed JSON ({:?} error at line {}, column {})",
+        .0.classify(),
+        .0.line(),
+        .0.column()
+    )]
+    Json(#[from] serde_json::Error),
+    #[error("a request with idempotency key `{0}` is already in progress")]
+    DuplicateRequest(String),
+    #[error("snapshot would grow to {size} bytes, the limit is {limit}")]
+    QuotaExceeded { size: u64, limit: u64 },
+    #[error("failed to encode or decode a store value")]
+    Codec(Redacted),
+    #[error("`{0}` runs several procedures and must be executed through `execute_procedure`")]
+    CompositeProcedure(&'static str),
+    #[error("not supported: {0}")]
+    Unsupported(&'static str),
+    /// The procedure didn't finish within its timeout, see
+    /// [`Builder::procedure_timeout`](crate::Builder::procedure_timeout).
+    #[error("procedure timed out after {} ms", .0.as_millis())]
+    Timeout(Duration),
+    #[error("the fee record holds {available} microcredits, the fee needs {required}")]
+    InsufficientFee { required: u64, available: u64 },
+    #[error("invalid wordlist: {0}")]
+    InvalidWordlist(String),
+    #[error("invalid mnemonic: {}", describe_mnemonic_error(.0))]
+    Mnemonic(crypto::keys::bip39::wordlist::Error),
+    /// An error returned by a [`Worker`](crate::worker::Worker) process, or the loss of it.
+    #[error("stronghold worker: {0}")]
+    Worker(String),
+    #[error("failed to shut down {} snapshot(s): {}", .0.len(), describe_failures(.0))]
+    Shutdown(Vec<(PathBuf, Error)>),
+}
+
+/// The text of an error that may quote secret input, such as a record or a word of a
+/// mnemonic. Displayed as `<redacted>`, see [`Error::debug_detail`].
+#[derive(Clone)]
+pub struct Redacted(String);
+
+impl Redacted {
+    pub(crate) fn new(detail: impl Into<String>) -> Self {
+        Self(detail.into())
+    }
+}
+
+impl fmt::Display for Redacted {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str("<redacted>")
+    }
+}
+
+impl fmt::Debug for Redacted {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str("Redacted(..)")
+    }
+}
+
+/// Like a derived `Debug`, but [`Error::Json`] and [`Error::Mnemonic`] leave out the input
+/// they may quote, as their display does, see [`Error::debug_detail`].
+impl fmt::Debug for Error {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::StrongholdNotInitialized => f.write_str("StrongholdNotInitialized"),
+            Self::AlreadyInitialized => f.write_str("AlreadyInitialized"),
+            Self::RecordExists => f.write_str("RecordExists"),
+            Self::TooManyAttempts { retry_after } => f
+                .debug_struct("TooManyAttempts")
+                .field("retry_after", retry_after)
+                .finish(),
+            Self::LegacyHashFunctionMissing => f.write_str("LegacyHashFunctionMissing"),
+            Self::PasswordMismatch => f.write_str("PasswordMismatch"),
+            Self::PasswordVerifierMissing => f.write_str("PasswordVerifierMissing"),
+            Self::SnapshotNotFound => f.write_str("SnapshotNotFound"),
+            Self::StoreRecordNotFound => f.write_str("StoreRecordNotFound"),
+            Self::Conflict => f.write_str("Conflict"),
+            Self::NotASnapshot => f.write_str("NotASnapshot"),
+            Self::Corrupted => f.write_str("Corrupted"),
+            Self::IncompatibleSnapshotVersion { found, expected } => f
+                .debug_struct("IncompatibleSnapshotVersion")
+                .field("found", found)
+                .field("expected", expected)
+                .finish(),
+            Self::Snapshot {
+                operation,
+                path,
+                source,
+            } => f
+                .debug_struct("Snapshot")
+                .field("operation", operation)
+                .field("path", path)
+                .field("source", source)
+                .finish(),
+            Self::ProcedureFailed { procedure, source } => f
+                .debug_struct("ProcedureFailed")
+                .field("procedure", procedure)
+                .field("source", source)
+                .finish(),
+            Self::BatchProcedure { index, source } => f
+                .debug_struct("BatchProcedure")
+                .field("index", index)
+                .field("source", source)
+                .finish(),
+            Self::InvalidInput { field, detail } => f
+                .debug_struct("InvalidInput")
+                .field("field", field)
+                .field("detail", detail)
+                .finish(),
+            Self::InvalidValue { field, detail } => f
+                .debug_struct("InvalidValue")
+                .field("field", field)
+                .field("detail", detail)
+                .finish(),
+            Self::InputTooLarge {
+                field,
+                size,
+                unit,
+                limit,
+            } => f
+                .debug_struct("InputTooLarge")
+                .field("field", field)
+                .field("size", size)
+                .field("unit", unit)
+                .field("limit", limit)
+                .finish(),
+            Self::InputCountMismatch { expected, got } => f
+                .debug_struct("InputCountMismatch")
+                .field("expected", expected)
+                .field("got", got)
+                .finish(),
+            Self::InputTypeMismatch {
+                index,
+                expected,
+                got,
+            } => f
+                .debug_struct("InputTypeMismatch")
+                .field("index", index)
+                .field("expected", expected)
+                .field("got", got)
+                .finish(),
+            Self::Stronghold(e) => f.debug_tuple("Stronghold").field(e).finish(),
+            Self::Memory(e) => f.debug_tuple("Memory").field(e).finish(),
+            Self::Procedure(e) => f.debug_tuple("Procedure").field(e).finish(),
+            Self::Crypto(e) => f.debug_tuple("Crypto").field(e).finish(),
+            Self::Io(e) => f.debug_tuple("Io").field(e).finish(),
+            Self::IoTransient(e) => f.debug_tuple("IoTransient").field(e).finish(),
+            Self::IoPermanent(e) => f.debug_tuple("IoPermanent").field(e).finish(),
+            Self::Json(e) => f
+                .debug_struct("Json")
+                .field("category", &e.classify())
+                .field("line", &e.line())
+                .field("column", &e.column())
+                .finish_non_exhaustive(),
+            Self::DuplicateRequest(key) => f.debug_tuple("DuplicateRequest").field(key).finish(),
+            Self::QuotaExceeded { size, limit } => f
+                .debug_struct("QuotaExceeded")
+                .field("size", size)
+                .field("limit", limit)
+                .finish(),
+            Self::Codec(detail) => f.debug_tuple("Codec").field(detail).finish(),
+            Self::CompositeProcedure(name) => {
+                f.debug_tuple("CompositeProcedure").field(name).finish()
+            }
+            Self::Unsupported(what) => f.debug_tuple("Unsupported").field(what).finish(),
+            Self::Timeout(timeout) => f.debug_tuple("Timeout").field(timeout).finish(),
+            Self::InsufficientFee {
+                required,
+                available,
+            } => f
+                .debug_struct("InsufficientFee")
+                .field("required", required)
+                .field("available", available)
+                .finish(),
+            Self::InvalidWordlist(detail) => {
+                f.debug_tuple("InvalidWordlist").field(detail).finish()
+            }
+            Self::Mnemonic(e) => f
+                .debug_tuple("Mnemonic")
+                .field(&describe_mnemonic_error(e))
+                .finish(),
+            Self::Worker(detail) => f.debug_tuple("Worker").field(detail).finish(),
+            Self::Shutdown(failures) => f.debug_tuple("Shutdown").field(failures).finish(),
+        }
+    }
+}
+
+/// Describes `e` without the word of the phrase it may carry.
+fn describe_mnemonic_error(e: &crypto::keys::bip39::wordlist::Error) -> String {
+    use crypto::keys::bip39::wordlist::Error as WordlistError;
+
+    match e {
+        WordlistError::NoSuchWord(_) => "a word is not in the wordlist".into(),
+        WordlistError::ChecksumMismatch => "the checksum does not match".into(),
+        WordlistError::InvalidEntropyCount(count) => format!("invalid entropy count {count}"),
+        _ => "malformed phrase".into(),
+    }
+}
+
+fn describe_failures(failures: &[(PathBuf, Error)]) -> String {
+    failures
+        .iter()
+        .map(|(path, e)| match e {
+            // Already names the path.
+            Error::Snapshot { .. } => e.to_string(),
+            e => format!("{}: {e}", path.display()),
+        })
+        .collect::<Vec<_>>()
+        .join("; ")
+}
+
+impl Error {
+    pub(crate) fn batch_procedure(index: usize, source: Error) -> Self {
+        Self::BatchProcedure {
+            index,
+            source: Box::new(source),
+        }
+    }
+
+    pub(crate) fn with_snapshot(self, operation: &'static str, path: &Path) -> Self {
+        Self::Snapshot {
+            operation,
+            path: path.to_path_buf(),
+            source: Box::new(self),
+        }
+    }
+
+    /// Names `procedure` in an error of the runtime. Errors of the plugin itself, like
+    /// invalid input, already tell what went wrong and are returned as they are.
+    pub(crate) fn in_procedure(self, procedure: &'static str) -> Self {
+        match self {
+            Self::Stronghold(_) | Self::Memory(_) | Self::Procedure(_) => Self::ProcedureFailed {
+                procedure,
+                source: Box::new(self),
+            },
+            e => e,
+        }
+    }
+
+    /// The error behind the context of [`Error::Snapshot`] and [`Error::ProcedureFailed`],
+    /// e.g. to tell an [`Error::IoTransient`] save failure from a permanent one.
+    pub fn root_cause(&self) -> &Error {
+        match self {
+            Self::Snapshot { source, .. } | Self::ProcedureFailed { source, .. } => {
+                source.root_cause()
+            }
+            e => e,
+        }
+    }
+
+    pub(crate) fn invalid_input(field: impl Into<String>, detail: impl Into<String>) -> Self {
+        Self::InvalidInput {
+            field: field.into(),
+            detail: detail.into(),
+        }
+    }
+
+    /// An [`Error::InvalidValue`], for `detail` quoting the value of `field`.
+    pub(crate) fn invalid_value(field: impl Into<String>, detail: impl Into<String>) -> Self {
+        Self::InvalidValue {
+            field: field.into(),
+            detail: Redacted::new(detail),
+        }
+    }
+
+    /// What the display of the error leaves out because it may quote secret input. Never log it
+    /// in production.
+    #[cfg(feature = "error-detail")]
+    pub fn debug_detail(&self) -> Option<String> {
+        match self {
+            Self::InvalidValue { detail, .. } | Self::Codec(detail) => Some(detail.0.clone()),
+            Self::Json(e) => Some(e.to_string()),
+            Self::Mnemonic(e) => Some(format!("{e:?}")),
+            Self::Snapshot { source, .. }
+            | Self::ProcedureFailed { source, .. }
+            | Self::BatchProcedure { source, .. } => source.debug_detail(),
+            _ => None,
+        }
+    }
 }
 
 impl Serialize for Error {
@@ -30,41 +441,1388 @@ impl Serialize for Error {
     }
 }
 
+/// Where a [`Stronghold`] persists its snapshot.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum Backend {
+    /// An encrypted snapshot file at the snapshot path.
+    #[default]
+    File,
+    /// Keeps the state in memory only, nothing is read from or written to disk.
+    #[cfg(feature = "memory-backend")]
+    Memory,
+    /// Keeps the encrypted snapshot in a [`SnapshotStore`], see [`Stronghold::with_store`].
+    Store,
+}
+
+/// Outcome of [`Stronghold::health_check`].
+#[derive(Serialize, Debug, Default)]
+#[serde(rename_all = "camelCase")]
+pub struct HealthReport {
+    /// Whether the snapshot file exists on disk, or in the [`SnapshotStore`] with
+    /// [`Backend::Store`].
+    pub snapshot_exists: bool,
+    /// Whether the snapshot file could be decrypted with the current key.
+    pub decrypted: bool,
+    /// Whether the plugin's metadata client could be read back from the file.
+    /// `None` if the snapshot has no metadata client.
+    pub metadata_readable: Option<bool>,
+    /// Paths of loaded clients whose store could not be read.
+    pub unreadable_clients: Vec<Vec<u8>>,
+    /// Human readable description of every failure.
+    pub errors: Vec<String>,
+}
+
+impl HealthReport {
+    pub fn is_healthy(&self) -> bool {
+        self.errors.is_empty()
+    }
+}
+
 pub struct Stronghold {
     inner: iota_stronghold::Stronghold,
     path: SnapshotPath,
     keyprovider: KeyProvider,
+    /// Key of the checksum written next to the snapshot file, derived from the snapshot key.
+    checksum_key: zeroize::Zeroizing<[u8; 32]>,
+    backend: Backend,
+    file_mode: Option<u32>,
+    max_size: Option<u64>,
+    /// Directory of the file a save is written to before it replaces the snapshot, see
+    /// [`with_temp_dir`](Self::with_temp_dir).
+    temp_dir: Option<PathBuf>,
+    /// Bytes written through [`reserve`](Self::reserve) since the last save.
+    pending_bytes: AtomicU64,
+    /// Time of the first write since the last save, see [`dirty_since`](Self::dirty_since).
+    dirty_since: Mutex<Option<SystemTime>>,
+    /// Set with [`Backend::Store`].
+    store: Option<Arc<dyn SnapshotStore>>,
+    /// Size of the snapshot in the `store` when it was last read or written.
+    stored_size: AtomicU64,
+    /// Loaded client handles, keyed by client path. Dropped together with the snapshot.
+    clients: Mutex<HashMap<Vec<u8>, Client>>,
+    /// Session entries, keyed by client path and store key. Never written to the snapshot.
+    session: Mutex<HashMap<(Vec<u8>, Vec<u8>), zeroize::Zeroizing<Vec<u8>>>>,
 }
 
 impl Stronghold {
     pub fn new<P: AsRef<Path>>(path: P, password: Vec<u8>) -> Result<Self> {
+        Self::with_backend(path, password, Backend::File)
+    }
+
+    pub fn with_backend<P: AsRef<Path>>(
+        path: P,
+        password: Vec<u8>,
+        backend: Backend,
+    ) -> Result<Self> {
+        if backend == Backend::Store {
+            return Err(Error::invalid_input(
+                "backend",
+                "a snapshot store must be given to `Stronghold::with_store`",
+            ));
+        }
         let path = SnapshotPath::from_path(path);
         let stronghold = iota_stronghold::Stronghold::default();
+        let checksum_key = checksum_key(&password);
         let keyprovider = KeyProvider::try_from(zeroize::Zeroizing::new(password))?;
-        if path.exists() {
-            stronghold.load_snapshot(&keyprovider, &path)?;
+        // A new snapshot file is written by the first save, even without a write.
+        let created = backend == Backend::File && !path.exists();
+        if backend == Backend::File && !created {
+            let found = snapshot_format_version(path.as_path()).map_err(|e| match e {
+                Error::Io(_) => e.with_snapshot("read", path.as_path()),
+                e => e,
+            })?;
+            if found != SNAPSHOT_FORMAT_VERSION {
+                return Err(Error::IncompatibleSnapshotVersion {
+                    found,
+                    expected: SNAPSHOT_FORMAT_VERSION,
+                });
+            }
+            if let Err(e) = stronghold.load_snapshot(&keyprovider, &path) {
+                // Tell a damaged file apart from a wrong password, which fails the key check.
+                if let Ok(ChecksumCheck::Mismatch) =
+                    check_checksum(path.as_path(), &checksum_key[..])
+                {
+                    return Err(Error::Corrupted);
+                }
+                return Err(e.into());
+            }
         }
         Ok(Self {
             inner: stronghold,
             path,
             keyprovider,
+            checksum_key,
+            backend,
+            file_mode: None,
+            max_size: None,
+            temp_dir: None,
+            pending_bytes: AtomicU64::new(0),
+            dirty_since: Mutex::new(created.then(SystemTime::now)),
+            store: None,
+            stored_size: AtomicU64::new(0),
+            clients: Default::default(),
+            session: Default::default(),
         })
     }
 
+    /// Opens the snapshot kept by `store` under `path`, starting an empty one if there is
+    /// none yet.
+    pub fn with_store<P: AsRef<Path>>(
+        path: P,
+        password: Vec<u8>,
+        store: Arc<dyn SnapshotStore>,
+    ) -> Result<Self> {
+        let path = SnapshotPath::from_path(path);
+        let stronghold = iota_stronghold::Stronghold::default();
+        let checksum_key = checksum_key(&password);
+        let keyprovider = KeyProvider::try_from(zeroize::Zeroizing::new(password))?;
+        let mut stored_size = 0;
+        let contents = read_from_store(store.as_ref(), &path)
+            .map_err(|e| e.with_snapshot("read", path.as_path()))?;
+        let created = contents.is_none();
+        if let Some(contents) = contents {
+            let found = parse_format_version(&contents)?;
+            if found != SNAPSHOT_FORMAT_VERSION {
+                return Err(Error::IncompatibleSnapshotVersion {
+                    found,
+                    expected: SNAPSHOT_FORMAT_VERSION,
+                });
+            }
+            stored_size = contents.len() as u64;
+            let staged = StagedSnapshot::with_contents(&contents)?;
+            stronghold.load_snapshot(&keyprovider, &staged.path())?;
+        }
+        Ok(Self {
+            inner: stronghold,
+            path,
+            keyprovider,
+            checksum_key,
+            backend: Backend::Store,
+            file_mode: None,
+            max_size: None,
+            temp_dir: None,
+            pending_bytes: AtomicU64::new(0),
+            dirty_since: Mutex::new(created.then(SystemTime::now)),
+            store: Some(store),
+            stored_size: AtomicU64::new(stored_size),
+            clients: Default::default(),
+            session: Default::default(),
+        })
+    }
+
+    /// Restricts the snapshot file, and the files staging its saves, to the Unix permission bits
+    /// `mode`, e.g. `0o600`. Ignored on Windows.
+    pub fn with_file_mode(mut self, mode: Option<u32>) -> Result<Self> {
+        self.file_mode = mode;
+        if self.backend == Backend::File && self.path.exists() {
+            self.apply_file_mode(self.path.as_path())?;
+        }
+        Ok(self)
+    }
+
+    /// Limits the size of the snapshot file to `max_size` bytes, see [`reserve`](Self::reserve).
+    pub fn with_max_size(mut self, max_size: Option<u64>) -> Self {
+        self.max_size = max_size;
+        self
+    }
+
+    /// Stages saves in `temp_dir` instead of next to the snapshot, unless it is on another device.
+    pub fn with_temp_dir(mut self, temp_dir: Option<PathBuf>) -> Self {
+        self.temp_dir = temp_dir;
+        self
+    }
+
+    /// Fails with [`Error::QuotaExceeded`] if a write of `bytes` would take the snapshot past its
+    /// size limit, estimated from the raw size of the writes since the last save.
+    pub fn reserve(&self, bytes: u64) -> Result<()> {
+        let Some(limit) = self.max_size else {
+            return Ok(());
+        };
+        let size = self.disk_size()? + self.pending_bytes.load(Ordering::Relaxed) + bytes;
+        if size > limit {
+            return Err(Error::QuotaExceeded { size, limit });
+        }
+        self.pending_bytes.fetch_add(bytes, Ordering::Relaxed);
+        Ok(())
+    }
+
+    fn disk_size(&self) -> Result<u64> {
+        if self.store.is_some() {
+            return Ok(self.stored_size.load(Ordering::Relaxed));
+        }
+        match std::fs::metadata(self.path.as_path()) {
+            Ok(metadata) => Ok(metadata.len()),
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => Ok(0),
+            Err(e) => Err(e.into()),
+        }
+    }
+
+    pub fn backend(&self) -> Backend {
+        self.backend
+    }
+
+    /// Records a write that the next [`save`](Self::save) has to persist, e.g. one made through a
+    /// [`client`](Self::client) handle.
+    pub fn mark_dirty(&self) {
+        self.dirty_since
+            .lock()
+            .unwrap()
+            .get_or_insert_with(SystemTime::now);
+    }
+
+    /// When the first write since the last save happened, `None` if there is none.
+    pub fn dirty_since(&self) -> Option<SystemTime> {
+        *self.dirty_since.lock().unwrap()
+    }
+
+    /// Writes the snapshot file if it was [marked dirty](Self::mark_dirty), retrying transient I/O
+    /// errors up to [`SAVE_ATTEMPTS`] times.
     pub fn save(&self) -> Result<()> {
+        if self.dirty_since().is_none() {
+            return Ok(());
+        }
+        self.force_save()
+    }
+
+    /// [`save`](Self::save), writing the snapshot file even if nothing changed, e.g. to
+    /// restore a file that was deleted or replaced from outside.
+    pub fn force_save(&self) -> Result<()> {
+        self.write_snapshot()
+            .map_err(|e| e.with_snapshot("save", self.path.as_path()))
+    }
+
+    fn write_snapshot(&self) -> Result<()> {
+        if let Some(store) = &self.store {
+            return self.commit_clean(|| {
+                retry_transient(|| self.commit_to_store(store.as_ref(), &self.keyprovider))
+            });
+        }
+        if self.backend != Backend::File {
+            return Ok(());
+        }
+        self.commit_clean(|| {
+            retry_transient(|| self.commit_staged(&self.keyprovider, &self.checksum_key[..]))
+        })
+    }
+
+    /// Runs `commit` with the snapshot marked clean, so that a write landing while it runs
+    /// keeps the snapshot dirty for the next save. Marked dirty again if `commit` fails.
+    fn commit_clean(&self, commit: impl FnOnce() -> Result<()>) -> Result<()> {
+        let since = self.dirty_since.lock().unwrap().take();
+        let result = commit();
+        match (&result, since) {
+            (Ok(()), _) => self.pending_bytes.store(0, Ordering::Relaxed),
+            (Err(_), Some(since)) => {
+                let mut dirty_since = self.dirty_since.lock().unwrap();
+                *dirty_since = Some(dirty_since.map_or(since, |later| later.min(since)));
+            }
+            (Err(_), None) => {}
+        }
+        result
+    }
+
+    /// Writes the snapshot and its checksum to staged files that replace the current ones if the
+    /// snapshot fits in the size limit.
+    fn commit_staged(&self, keyprovider: &KeyProvider, checksum_key: &[u8]) -> Result<()> {
+        let staged = self.staging_path();
+        create_staged_file(&staged, self.file_mode)?;
+        self.inner
+            .commit_with_keyprovider(&SnapshotPath::from_path(&staged), keyprovider)?;
+        // The runtime may have replaced the file it was given rather than writing into it.
+        self.apply_file_mode(&staged)?;
+        let contents = std::fs::read(&staged)?;
+        let size = contents.len() as u64;
+        if let Some(limit) = self.max_size.filter(|&limit| size > limit) {
+            std::fs::remove_file(&staged)?;
+            return Err(Error::QuotaExceeded { size, limit });
+        }
+        let checksum = staged_checksum_path(self.path.as_path());
+        create_staged_file(&checksum, self.file_mode)?;
+        std::fs::write(&checksum, checksum_file(checksum_key, &contents)?)?;
+        self.apply_file_mode(&checksum)?;
+        std::fs::rename(&staged, self.path.as_path())?;
+        std::fs::rename(&checksum, checksum_path(self.path.as_path()))?;
+        Ok(())
+    }
+
+    /// Checks the snapshot file against the keyed checksum of its last save, `false` if there is
+    /// none. Fails with [`Error::Corrupted`] if they don't match.
+    pub fn verify_integrity(&self) -> Result<bool> {
+        if self.backend != Backend::File {
+            return Ok(false);
+        }
+        if !self.path.as_path().is_file() {
+            return Err(Error::SnapshotNotFound);
+        }
+        match check_checksum(self.path.as_path(), &self.checksum_key[..])? {
+            ChecksumCheck::Missing => Ok(false),
+            ChecksumCheck::Valid => Ok(true),
+            ChecksumCheck::WrongKey | ChecksumCheck::Mismatch => Err(Error::Corrupted),
+        }
+    }
+
+    /// The file a save is staged in: in the temp dir if it is on the device of the snapshot,
+    /// next to the snapshot otherwise.
+    fn staging_path(&self) -> PathBuf {
+        let path = self.path.as_path();
+        let mut name = path.file_name().unwrap_or_default().to_owned();
+        name.push(".staged");
+        let parent = match path.parent() {
+            Some(parent) if !parent.as_os_str().is_empty() => parent,
+            _ => Path::new("."),
+        };
+        match &self.temp_dir {
+            Some(temp_dir) if same_device(temp_dir, parent) => {
+                // Snapshots of the same name in different directories share the temp dir.
+                let mut digest = [0u8; 32];
+                SHA256(path.as_os_str().as_encoded_bytes(), &mut digest);
+                let mut unique = std::ffi::OsString::from(hex::encode(&digest[..8]));
+                unique.push("-");
+                unique.push(name);
+                temp_dir.join(unique)
+            }
+            Some(temp_dir) => {
+                log::warn!(
+                    "temp dir {} is not on the device of {}, staging the save next to it",
+                    temp_dir.display(),
+                    path.display()
+                );
+                parent.join(name)
+            }
+            None => parent.join(name),
+        }
+    }
+
+    /// Encrypts the snapshot with `keyprovider` and hands it to `store`, if it fits in the
+    /// size limit.
+    fn commit_to_store(&self, store: &dyn SnapshotStore, keyprovider: &KeyProvider) -> Result<()> {
+        let staged = StagedSnapshot::new()?;
         self.inner
-            .commit_with_keyprovider(&self.path, &self.keyprovider)?;
+            .commit_with_keyprovider(&staged.path(), keyprovider)?;
+        let contents = std::fs::read(&staged.0)?;
+        let size = contents.len() as u64;
+        if let Some(limit) = self.max_size {
+            if size > limit {
+                return Err(Error::QuotaExceeded { size, limit });
+            }
+        }
+        store.write(&store_key(&self.path), &contents)?;
+        self.stored_size.store(size, Ordering::Relaxed);
+        Ok(())
+    }
+
+    /// Sets the [`file_mode`](Self::with_file_mode) on the file at `path`, which is either
+    /// the snapshot or a save staged for it.
+    #[cfg(unix)]
+    fn apply_file_mode(&self, path: &Path) -> Result<()> {
+        use std::os::unix::fs::PermissionsExt;
+
+        if let Some(mode) = self.file_mode {
+            std::fs::set_permissions(path, std::fs::Permissions::from_mode(mode))?;
+        }
+        Ok(())
+    }
+
+    #[cfg(not(unix))]
+    fn apply_file_mode(&self, _path: &Path) -> Result<()> {
+        Ok(())
+    }
+
+    /// Re-encrypts the snapshot under `password` and saves it, keeping the current key if saving
+    /// fails.
+    pub fn rekey(&mut self, password: Vec<u8>) -> Result<()> {
+        let checksum_key = checksum_key(&password);
+        let keyprovider = KeyProvider::try_from(zeroize::Zeroizing::new(password))?;
+        self.commit_with(&keyprovider, &checksum_key[..])
+            .map_err(|e| e.with_snapshot("re-encrypt", self.path.as_path()))?;
+        self.keyprovider = keyprovider;
+        self.checksum_key = checksum_key;
+        *self.dirty_since.lock().unwrap() = None;
+        Ok(())
+    }
+
+    fn commit_with(&self, keyprovider: &KeyProvider, checksum_key: &[u8]) -> Result<()> {
+        if let Some(store) = &self.store {
+            self.commit_to_store(store.as_ref(), keyprovider)?;
+        } else if self.backend == Backend::File {
+            self.commit_staged(keyprovider, checksum_key)?;
+        }
+        Ok(())
+    }
+
+    /// Copies the snapshot of the [`SnapshotStore`] to a staged file, `None` if there is none.
+    fn stage_stored_snapshot(&self) -> Result<Option<StagedSnapshot>> {
+        let Some(store) = &self.store else {
+            return Ok(None);
+        };
+        read_from_store(store.as_ref(), &self.path)?
+            .map(|contents| StagedSnapshot::with_contents(&contents))
+            .transpose()
+    }
+
+    /// Replaces the in-memory state of `client_path` with the one stored in the snapshot file.
+    pub fn reload_client<P: AsRef<[u8]>>(&self, client_path: P) -> Result<()> {
+        let context = |e: Error| e.with_snapshot("reload a client from", self.path.as_path());
+        let client = if self.store.is_some() {
+            let staged = self
+                .stage_stored_snapshot()
+                .map_err(context)?
+                .ok_or(Error::SnapshotNotFound)?;
+            self.inner
+                .load_client_from_snapshot(&client_path, &self.keyprovider, &staged.path())
+                .map_err(|e| context(e.into()))?
+        } else {
+            if self.backend != Backend::File || !self.path.exists() {
+                return Err(Error::SnapshotNotFound);
+            }
+            self.inner
+                .load_client_from_snapshot(&client_path, &self.keyprovider, &self.path)
+                .map_err(|e| context(e.into()))?
+        };
+        self.clients
+            .lock()
+            .unwrap()
+            .insert(client_path.as_ref().to_vec(), client);
+        Ok(())
+    }
+
+    /// Copies `clients` of `source` into this snapshot, replacing clients of the same path. Nothing
+    /// is saved.
+    pub fn import_clients(&self, source: &Stronghold, clients: &[Vec<u8>]) -> Result<()> {
+        if clients.iter().any(|client| client == METADATA_CLIENT) {
+            return Err(Error::invalid_input(
+                "clients",
+                "the metadata client of the plugin can't be copied",
+            ));
+        }
+        let source_file = source.commit_to_temp(&source.inner, &source.keyprovider)?;
+        // The state of this snapshot is loaded back through its metadata client.
+        self.metadata_client()?;
+        let target_file = self.commit_to_temp(&self.inner, &self.keyprovider)?;
+
+        let merged = iota_stronghold::Stronghold::default();
+        for client in clients {
+            merged.load_client_from_snapshot(client, &source.keyprovider, &source_file.path())?;
+        }
+        // Replaces the state read from `source`, keeping the clients loaded from it.
+        merged.load_client_from_snapshot(
+            METADATA_CLIENT,
+            &self.keyprovider,
+            &target_file.path(),
+        )?;
+        let merged_file = self.commit_to_temp(&merged, &self.keyprovider);
+        merged.clear()?;
+        let merged_file = merged_file?;
+
+        for client in clients {
+            let handle = self.inner.load_client_from_snapshot(
+                client,
+                &self.keyprovider,
+                &merged_file.path(),
+            )?;
+            self.clients.lock().unwrap().insert(client.clone(), handle);
+            self.copy_client_metadata(source, client)?;
+        }
+        self.mark_dirty();
         Ok(())
     }
 
+    /// Writes `runtime` with `keyprovider` to a [`StagedSnapshot`] in the
+    /// [`temp_dir`](Self::with_temp_dir), or next to the snapshot without one, with the
+    /// [`file_mode`](Self::with_file_mode), or `0o600` without one.
+    fn commit_to_temp(
+        &self,
+        runtime: &iota_stronghold::Stronghold,
+        keyprovider: &KeyProvider,
+    ) -> Result<StagedSnapshot> {
+        let dir = match (&self.temp_dir, self.path.as_path().parent()) {
+            (Some(temp_dir), _) => temp_dir.as_path(),
+            (None, Some(parent)) if !parent.as_os_str().is_empty() => parent,
+            (None, _) => Path::new("."),
+        };
+        let staged = StagedSnapshot::new_in(dir)?;
+        let mode = self.file_mode.unwrap_or(0o600);
+        create_staged_file(&staged.0, Some(mode))?;
+        runtime.commit_with_keyprovider(&staged.path(), keyprovider)?;
+        // The runtime may have replaced the file it was given rather than writing into it.
+        #[cfg(unix)]
+        std::fs::set_permissions(
+            &staged.0,
+            std::os::unix::fs::PermissionsExt::from_mode(mode),
+        )?;
+        Ok(staged)
+    }
+
+    /// Copies the record index, counters and store expiries of `client` from `source`.
+    fn copy_client_metadata(&self, source: &Stronghold, client: &[u8]) -> Result<()> {
+        let from = source.metadata_client()?.store();
+        let to = self.metadata_client()?.store();
+        for key in from.keys()? {
+            let Some(prefix) = [RECORD_INDEX_PREFIX, COUNTER_PREFIX, STORE_EXPIRY_PREFIX]
+                .into_iter()
+                .find(|prefix| key.starts_with(prefix))
+            else {
+                continue;
+            };
+            match parse_metadata_name(&key[prefix.len()..]) {
+                Some((owner, _)) if owner == client => {}
+                _ => continue,
+            }
+            // Entries can expire between listing the keys and reading them.
+            let Some(value) = from.get(&key)? else {
+                continue;
+            };
+            let lifetime = if prefix == STORE_EXPIRY_PREFIX {
+                let Some(expiry) = <[u8; 8]>::try_from(value.as_slice()).ok() else {
+                    continue;
+                };
+                let expiry = UNIX_EPOCH + Duration::from_millis(u64::from_le_bytes(expiry));
+                match expiry.duration_since(SystemTime::now()) {
+                    Ok(lifetime) => Some(lifetime),
+                    Err(_) => continue,
+                }
+            } else {
+                None
+            };
+            to.insert(key, value, lifetime)?;
+        }
+        Ok(())
+    }
+
+    /// Returns the handle of a loaded client, reusing a cached one when possible.
+    pub fn client<P: AsRef<[u8]>>(&self, client_path: P) -> Result<Client> {
+        let mut clients = self.clients.lock().unwrap();
+        if let Some(client) = clients.get(client_path.as_ref()) {
+            return Ok(client.clone());
+        }
+        let client = self.inner.get_client(&client_path)?;
+        clients.insert(client_path.as_ref().to_vec(), client.clone());
+        Ok(client)
+    }
+
+    /// Drops the cached handle of `client_path`, if any.
+    pub fn forget_client<P: AsRef<[u8]>>(&self, client_path: P) {
+        self.clients.lock().unwrap().remove(client_path.as_ref());
+    }
+
+    /// Drops the clients and session entries held in memory. Unsaved changes are lost.
+    pub fn clear(&self) -> Result<()> {
+        self.clients.lock().unwrap().clear();
+        self.session.lock().unwrap().clear();
+        self.inner.clear()?;
+        Ok(())
+    }
+
+    /// Keeps `value` at `key` of `client` until the snapshot is unloaded, returning the previous
+    /// value. Session entries are never saved.
+    pub fn insert_session_entry(
+        &self,
+        client: &[u8],
+        key: &[u8],
+        value: Vec<u8>,
+    ) -> Option<Vec<u8>> {
+        self.session
+            .lock()
+            .unwrap()
+            .insert((client.to_vec(), key.to_vec()), value.into())
+            .map(|previous| previous.to_vec())
+    }
+
+    pub fn session_entry(&self, client: &[u8], key: &[u8]) -> Option<Vec<u8>> {
+        self.session
+            .lock()
+            .unwrap()
+            .get(&(client.to_vec(), key.to_vec()))
+            .map(|value| value.to_vec())
+    }
+
+    pub fn remove_session_entry(&self, client: &[u8], key: &[u8]) -> Option<Vec<u8>> {
+        self.session
+            .lock()
+            .unwrap()
+            .remove(&(client.to_vec(), key.to_vec()))
+            .map(|previous| previous.to_vec())
+    }
+
+    /// Returns the metadata client, loading or creating it as needed.
+    pub(crate) fn metadata_client(&self) -> Result<Client> {
+        if let Ok(client) = self.inner.get_client(METADATA_CLIENT) {
+            return Ok(client);
+        }
+        match self.inner.load_client(METADATA_CLIENT) {
+            Ok(client) => Ok(client),
+            Err(_) => self
+                .inner
+                .create_client(METADATA_CLIENT)
+                .map_err(Into::into),
+        }
+    }
+
+    /// Record paths written to `vault` of `client` through the plugin, see [`Self::index_record`].
+    pub fn indexed_records(&self, client: &[u8], vault: &[u8]) -> Result<BTreeSet<Vec<u8>>> {
+        let key = metadata_key(RECORD_INDEX_PREFIX, client, vault);
+        match self.metadata_client()?.store().get(&key)? {
+            Some(index) => Ok(serde_json::from_slice(&index)?),
+            None => Ok(BTreeSet::new()),
+        }
+    }
+
+    /// Vaults of `client` with at least one record in their index.
+    pub fn indexed_vaults(&self, client: &[u8]) -> Result<BTreeSet<Vec<u8>>> {
+        Ok(self
+            .record_indexes()?
+            .into_iter()
+            .filter(|(indexed, _)| indexed == client)
+            .map(|(_, vault)| vault)
+            .collect())
+    }
+
+    /// Clients with at least one indexed record, together with the clients loaded so far.
+    pub fn indexed_clients(&self) -> Result<BTreeSet<Vec<u8>>> {
+        let mut clients: BTreeSet<_> = self
+            .record_indexes()?
+            .into_iter()
+            .map(|(client, _)| client)
+            .collect();
+        clients.extend(self.clients.lock().unwrap().keys().cloned());
+        Ok(clients)
+    }
+
+    /// Client and vault of every non-empty record index.
+    fn record_indexes(&self) -> Result<Vec<(Vec<u8>, Vec<u8>)>> {
+        let store = self.metadata_client()?.store();
+        let mut indexes = Vec::new();
+        for key in store.keys()? {
+            let Some((client, vault)) = key
+                .strip_prefix(RECORD_INDEX_PREFIX)
+                .and_then(parse_metadata_name)
+            else {
+                continue;
+            };
+            let Some(index) = store.get(&key)? else {
+                continue;
+            };
+            if !serde_json::from_slice::<BTreeSet<Vec<u8>>>(&index)?.is_empty() {
+                indexes.push((client, vault));
+            }
+        }
+        Ok(indexes)
+    }
+
+    /// Adds `record` to the index of `vault`, or removes it if it is no longer `present`.
+    pub fn index_record(
+        &self,
+        client: &[u8],
+        vault: &[u8],
+        record: &[u8],
+        present: bool,
+    ) -> Result<()> {
+        let mut index = self.indexed_records(client, vault)?;
+        let changed = if present {
+            index.insert(record.to_vec())
+        } else {
+            index.remove(record)
+        };
+        if changed {
+            self.metadata_client()?.store().insert(
+                metadata_key(RECORD_INDEX_PREFIX, client, vault),
+                serde_json::to_vec(&index)?,
+                None,
+            )?;
+            self.mark_dirty();
+        }
+        Ok(())
+    }
+
+    /// Records when the store record `key` of `client` expires, `None` if it doesn't.
+    pub fn set_store_expiry(
+        &self,
+        client: &[u8],
+        key: &[u8],
+        lifetime: Option<Duration>,
+    ) -> Result<()> {
+        let meta_key = metadata_key(STORE_EXPIRY_PREFIX, client, key);
+        let store = self.metadata_client()?.store();
+        match lifetime {
+            Some(lifetime) => {
+                let expiry = (SystemTime::now() + lifetime)
+                    .duration_since(UNIX_EPOCH)
+                    .unwrap_or_default()
+                    .as_millis() as u64;
+                // Expires together with the record it describes.
+                store.insert(meta_key, expiry.to_le_bytes().to_vec(), Some(lifetime))?;
+            }
+            None => {
+                store.delete(&meta_key)?;
+            }
+        }
+        self.mark_dirty();
+        Ok(())
+    }
+
+    /// Increments the counter of `vault` in `client`, kept in the snapshot metadata, and returns
+    /// its new value. The caller must hold the snapshot lock.
+    pub fn next_counter(&self, client: &[u8], vault: &[u8]) -> Result<usize> {
+        let meta_key = metadata_key(COUNTER_PREFIX, client, vault);
+        let store = self.metadata_client()?.store();
+        let current = match store.get(&meta_key)? {
+            Some(bytes) => <[u8; 8]>::try_from(bytes.as_slice())
+                .map(u64::from_le_bytes)
+                .map_err(|_| Error::invalid_input("counter", "corrupted metadata"))?,
+            None => 0,
+        };
+        let next = current
+            .checked_add(1)
+            .and_then(|next| usize::try_from(next).ok())
+            .ok_or_else(|| Error::invalid_input("counter", "is exhausted"))?;
+        store.insert(meta_key, (next as u64).to_le_bytes().to_vec(), None)?;
+        self.mark_dirty();
+        Ok(next)
+    }
+
+    /// When the store record `key` of `client` expires, if it was saved with a lifetime.
+    pub fn store_expiry(&self, client: &[u8], key: &[u8]) -> Result<Option<SystemTime>> {
+        let meta_key = metadata_key(STORE_EXPIRY_PREFIX, client, key);
+        let Some(expiry) = self.metadata_client()?.store().get(&meta_key)? else {
+            return Ok(None);
+        };
+        let expiry = <[u8; 8]>::try_from(expiry.as_slice())
+            .map_err(|_| Error::invalid_input("store expiry", "corrupted metadata"))?;
+        Ok(Some(
+            UNIX_EPOCH + Duration::from_millis(u64::from_le_bytes(expiry)),
+        ))
+    }
+
+    /// Stores a salted digest of the derived `key` so it can be checked by [`Self::verify_key`].
+    pub fn set_password_verifier(&self, key: &[u8]) -> Result<()> {
+        let mut verifier = vec![0u8; VERIFIER_SALT_LENGTH];
+        crypto::utils::rand::fill(&mut verifier)?;
+        let digest = verifier_digest(&verifier, key);
+        verifier.extend_from_slice(&digest);
+        self.metadata_client()?
+            .store()
+            .insert(PASSWORD_VERIFIER_KEY.to_vec(), verifier, None)?;
+        self.mark_dirty();
+        Ok(())
+    }
+
+    /// Checks the derived `key` against the stored verifier, `None` if none was stored.
+    pub fn verify_key(&self, key: &[u8]) -> Result<Option<bool>> {
+        let Some(verifier) = self.metadata_client()?.store().get(PASSWORD_VERIFIER_KEY)? else {
+            return Ok(None);
+        };
+        if verifier.len() <= VERIFIER_SALT_LENGTH {
+            return Ok(Some(false));
+        }
+        let (salt, expected) = verifier.split_at(VERIFIER_SALT_LENGTH);
+        let actual = verifier_digest(salt, key);
+        // Compare without short-circuiting on the first differing byte.
+        let diff = actual
+            .iter()
+            .zip(expected)
+            .fold(expected.len() ^ actual.len(), |acc, (a, b)| {
+                acc | usize::from(a ^ b)
+            });
+        Ok(Some(diff == 0))
+    }
+
+    /// Checks, in a throwaway instance, that the snapshot file decrypts with the current key and
+    /// can be read.
+    pub fn health_check(&self) -> HealthReport {
+        let mut report = HealthReport::default();
+
+        for (path, client) in self.clients.lock().unwrap().iter() {
+            if let Err(e) = client.store().keys() {
+                report.unreadable_clients.push(path.clone());
+                report
+                    .errors
+                    .push(format!("loaded client is unreadable: {e}"));
+            }
+        }
+
+        let staged = match self.stage_stored_snapshot() {
+            Ok(staged) => staged,
+            Err(e) => {
+                report.errors.push(format!("failed to read snapshot: {e}"));
+                return report;
+            }
+        };
+        if self.backend != Backend::File && staged.is_none() {
+            return report;
+        }
+
+        report.snapshot_exists = staged.is_some() || self.path.exists();
+        if !report.snapshot_exists {
+            // Nothing was saved yet, which is not a failure.
+            return report;
+        }
+
+        let path = staged.as_ref().map_or_else(
+            || SnapshotPath::from_path(self.path.as_path()),
+            StagedSnapshot::path,
+        );
+        let probe = iota_stronghold::Stronghold::default();
+        if let Err(e) = probe.load_snapshot(&self.keyprovider, &path) {
+            report
+                .errors
+                .push(format!("failed to decrypt snapshot: {e}"));
+            return report;
+        }
+        report.decrypted = true;
+
+        if let Ok(client) = probe.load_client(METADATA_CLIENT) {
+            let readable = client.store().keys().is_ok();
+            if !readable {
+                report.errors.push("metadata client is unreadable".into());
+            }
+            report.metadata_readable = Some(readable);
+        }
+
+        report
+    }
+
     pub fn inner(&self) -> &iota_stronghold::Stronghold {
         &self.inner
     }
 }
 
+/// Metadata store key for something about `name` in `client`.
+fn metadata_key(prefix: &[u8], client: &[u8], name: &[u8]) -> Vec<u8> {
+    let mut key = prefix.to_vec();
+    key.extend_from_slice(hex::encode(client).as_bytes());
+    key.push(b':');
+    key.extend_from_slice(hex::encode(name).as_bytes());
+    key
+}
+
+/// Splits the `client:name` part of a [`metadata_key`] back into its decoded halves.
+fn parse_metadata_name(key: &[u8]) -> Option<(Vec<u8>, Vec<u8>)> {
+    let separator = key.iter().position(|&b| b == b':')?;
+    let client = hex::decode(&key[..separator]).ok()?;
+    let name = hex::decode(&key[separator + 1..]).ok()?;
+    Some((client, name))
+}
+
+/// Whether a file exists at `path`. Nothing is read, see [`snapshot_format_version`].
+pub fn snapshot_exists_on_disk(path: &Path) -> bool {
+    path.is_file()
+}
+
+/// Reads the format version from the header of the snapshot file at `path`, without decrypting it.
+pub fn snapshot_format_version(path: &Path) -> Result<[u8; 2]> {
+    let mut header = [0u8; SNAPSHOT_MAGIC.len() + 2];
+    let mut file = std::fs::File::open(path)?;
+    match file.read_exact(&mut header) {
+        Ok(()) => {}
+        Err(e) if e.kind() == std::io::ErrorKind::UnexpectedEof => return Err(Error::NotASnapshot),
+        Err(e) => return Err(e.into()),
+    }
+    parse_format_version(&header)
+}
+
+/// Reads the format version from `contents`, which start with a snapshot header.
+fn parse_format_version(contents: &[u8]) -> Result<[u8; 2]> {
+    let header = contents
+        .get(..SNAPSHOT_MAGIC.len() + 2)
+        .ok_or(Error::NotASnapshot)?;
+    if header[..SNAPSHOT_MAGIC.len()] != SNAPSHOT_MAGIC {
+        return Err(Error::NotASnapshot);
+    }
+    Ok([
+        header[SNAPSHOT_MAGIC.len()],
+        header[SNAPSHOT_MAGIC.len() + 1],
+    ])
+}
+
+/// Runs `write` until it succeeds, fails with something else than a transient I/O error, or
+/// ran [`SAVE_ATTEMPTS`] times.
+fn retry_transient(mut write: impl FnMut() -> Result<()>) -> Result<()> {
+    let mut backoff = SAVE_RETRY_BACKOFF;
+    let mut attempt = 1;
+    loop {
+        let e = match write() {
+            Ok(()) => return Ok(()),
+            Err(e) => e,
+        };
+        let io = match into_io_error(e) {
+            Ok(io) => io,
+            Err(e) => return Err(e),
+        };
+        if !is_transient(io.kind()) {
+            return Err(Error::IoPermanent(io));
+        }
+        if attempt == SAVE_ATTEMPTS {
+            return Err(Error::IoTransient(io));
+        }
+        log::warn!("failed to save snapshot, retrying in {backoff:?}: {io}");
+        std::thread::sleep(backoff);
+        backoff *= 2;
+        attempt += 1;
+    }
+}
+
+/// The I/O error behind `e`, also when the runtime wrapped it.
+fn into_io_error(e: Error) -> std::result::Result<std::io::Error, Error> {
+    if let Error::Io(io) = e {
+        return Ok(io);
+    }
+    let mut source = std::error::Error::source(&e);
+    while let Some(cause) = source {
+        if let Some(io) = cause.downcast_ref::<std::io::Error>() {
+            return Ok(std::io::Error::new(io.kind(), e.to_string()));
+        }
+        source = cause.source();
+    }
+    Err(e)
+}
+
+fn is_transient(kind: std::io::ErrorKind) -> bool {
+    use std::io::ErrorKind;
+
+    matches!(
+        kind,
+        ErrorKind::Interrupted | ErrorKind::WouldBlock | ErrorKind::TimedOut | ErrorKind::WriteZero
+    )
+}
+
+/// Key of the snapshot at `path` in a [`SnapshotStore`].
+fn store_key(path: &SnapshotPath) -> String {
+    path.as_path().to_string_lossy().into_owned()
+}
+
+/// The snapshot kept by `store` for `path`, if any.
+fn read_from_store(store: &dyn SnapshotStore, path: &SnapshotPath) -> Result<Option<Vec<u8>>> {
+    match store.read(&store_key(path)) {
+        Ok(contents) => Ok(Some(contents)),
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => Ok(None),
+        Err(e) => Err(e.into()),
+    }
+}
+
+/// Whether `a` and `b` are on the same device, so that a file renames atomically from one to
+/// the other. `false` if either can't be inspected.
+#[cfg(unix)]
+fn same_device(a: &Path, b: &Path) -> bool {
+    use std::os::unix::fs::MetadataExt;
+
+    match (std::fs::metadata(a), std::fs::metadata(b)) {
+        (Ok(a), Ok(b)) => a.dev() == b.dev(),
+        _ => false,
+    }
+}
+
+/// Without device numbers, compares the drives of both paths, which must exist.
+#[cfg(not(unix))]
+fn same_device(a: &Path, b: &Path) -> bool {
+    match (std::fs::canonicalize(a), std::fs::canonicalize(b)) {
+        (Ok(a), Ok(b)) => a.components().next() == b.components().next(),
+        _ => false,
+    }
+}
+
+/// A temporary file holding a snapshot on its way to or from a [`SnapshotStore`], since the
+/// runtime only reads and writes snapshots as files. Removed when dropped.
+struct StagedSnapshot(PathBuf);
+
+impl StagedSnapshot {
+    fn new() -> Result<Self> {
+        Self::new_in(&std::env::temp_dir())
+    }
+
+    /// A staged snapshot in `dir`, not created yet.
+    fn new_in(dir: &Path) -> Result<Self> {
+        let mut name = [0u8; 16];
+        crypto::utils::rand::fill(&mut name)?;
+        Ok(Self(dir.join(format!(
+            "tauri-plugin-stronghold-{}.snapshot",
+            hex::encode(name)
+        ))))
+    }
+
+    fn with_contents(contents: &[u8]) -> Result<Self> {
+        let staged = Self::new()?;
+        let mut options = std::fs::OpenOptions::new();
+        options.write(true).create_new(true);
+        #[cfg(unix)]
+        std::os::unix::fs::OpenOptionsExt::mode(&mut options, 0o600);
+        std::io::Write::write_all(&mut options.open(&staged.0)?, contents)?;
+        Ok(staged)
+    }
+
+    fn path(&self) -> SnapshotPath {
+        SnapshotPath::from_path(&self.0)
+    }
+}
+
+impl Drop for StagedSnapshot {
+    fn drop(&mut self) {
+        if let Err(e) = std::fs::remove_file(&self.0) {
+            if e.kind() != std::io::ErrorKind::NotFound {
+                log::warn!("failed to remove staged snapshot {:?}: {e}", self.0);
+            }
+        }
+    }
+}
+
+/// Creates or truncates the file a save is staged in, with the permission bits `mode` if
+/// given, so the snapshot it becomes never has other permissions.
+fn create_staged_file(path: &Path, mode: Option<u32>) -> Result<()> {
+    let mut options = std::fs::OpenOptions::new();
+    options.write(true).create(true).truncate(true);
+    #[cfg(unix)]
+    if let Some(mode) = mode {
+        std::os::unix::fs::OpenOptionsExt::mode(&mut options, mode);
+    }
+    #[cfg(not(unix))]
+    let _ = mode;
+    options.open(path)?;
+    Ok(())
+}
+
+fn checksum_path(path: &Path) -> PathBuf {
+    let mut checksum = path.as_os_str().to_owned();
+    checksum.push(CHECKSUM_SUFFIX);
+    PathBuf::from(checksum)
+}
+
+fn staged_checksum_path(path: &Path) -> PathBuf {
+    let mut staged = checksum_path(path).into_os_string();
+    staged.push(CHECKSUM_STAGED_SUFFIX);
+    PathBuf::from(staged)
+}
+
+/// The key of the snapshot checksum for the derived snapshot key `password`.
+fn checksum_key(password: &[u8]) -> zeroize::Zeroizing<[u8; 32]> {
+    let mut key = zeroize::Zeroizing::new([0u8; 32]);
+    HMAC_SHA256(CHECKSUM_KEY_LABEL, password, &mut *key);
+    key
+}
+
+/// The checksum file of the snapshot `contents`: [`CHECKSUM_FORMAT`], a random salt, a check
+/// value of `key` and the HMAC-SHA256 of the salt and `contents`, both keyed with `key`.
+fn checksum_file(key: &[u8], contents: &[u8]) -> Result<Vec<u8>> {
+    let mut salt = [0u8; CHECKSUM_SALT_LENGTH];
+    crypto::utils::rand::fill(&mut salt)?;
+    let mut checksum = vec![CHECKSUM_FORMAT];
+    checksum.extend_from_slice(&salt);
+    checksum.extend_from_slice(&key_check(key, &salt));
+    checksum.extend_from_slice(&checksum_digest(key, &salt, contents));
+    Ok(checksum)
+}
+
+/// What the checksum of a snapshot file says about it.
+#[derive(Debug, PartialEq, Eq)]
+enum ChecksumCheck {
+    /// There is no keyed checksum to check against.
+    Missing,
+    Valid,
+    /// The checksum was written with another key, i.e. another password.
+    WrongKey,
+    /// The contents don't match the checksum, or the checksum itself is damaged.
+    Mismatch,
+}
+
+/// Checks the snapshot file at `path` against its checksum, or against the staged checksum
+/// of a save interrupted before replacing the checksum.
+fn check_checksum(path: &Path, key: &[u8]) -> Result<ChecksumCheck> {
+    let contents = std::fs::read(path)?;
+    let check = check_checksum_file(&checksum_path(path), key, &contents)?;
+    if check == ChecksumCheck::Valid {
+        return Ok(check);
+    }
+    match check_checksum_file(&staged_checksum_path(path), key, &contents)? {
+        ChecksumCheck::Valid => Ok(ChecksumCheck::Valid),
+        _ => Ok(check),
+    }
+}
+
+fn check_checksum_file(checksum_path: &Path, key: &[u8], contents: &[u8]) -> Result<ChecksumCheck> {
+    let checksum = match std::fs::read(checksum_path) {
+        Ok(checksum) => checksum,
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => return Ok(ChecksumCheck::Missing),
+        Err(e) => return Err(e.into()),
+    };
+    let rest = match checksum.split_first() {
+        Some((&CHECKSUM_FORMAT, rest)) if rest.len() == CHECKSUM_SALT_LENGTH + 64 => rest,
+        // A salt and an unkeyed digest, as written by older releases.
+        _ if checksum.len() == CHECKSUM_SALT_LENGTH + 32 => return Ok(ChecksumCheck::Missing),
+        _ => return Ok(ChecksumCheck::Mismatch),
+    };
+    let (salt, rest) = rest.split_at(CHECKSUM_SALT_LENGTH);
+    let (expected_check, expected_digest) = rest.split_at(32);
+    if !digests_match(&key_check(key, salt), expected_check) {
+        return Ok(ChecksumCheck::WrongKey);
+    }
+    if !digests_match(&checksum_digest(key, salt, contents), expected_digest) {
+        return Ok(ChecksumCheck::Mismatch);
+    }
+    Ok(ChecksumCheck::Valid)
+}
+
+/// Tells whether a checksum was written with `key`, so that a mismatch can be told apart
+/// from a wrong password.
+fn key_check(key: &[u8], salt: &[u8]) -> [u8; 32] {
+    let mut data = b"key check".to_vec();
+    data.extend_from_slice(salt);
+    let mut digest = [0u8; 32];
+    HMAC_SHA256(&data, key, &mut digest);
+    digest
+}
+
+fn checksum_digest(key: &[u8], salt: &[u8], contents: &[u8]) -> [u8; 32] {
+    let mut data = Vec::with_capacity(salt.len() + contents.len());
+    data.extend_from_slice(salt);
+    data.extend_from_slice(contents);
+    let mut digest = [0u8; 32];
+    HMAC_SHA256(&data, key, &mut digest);
+    digest
+}
+
+/// Compares without short-circuiting on the first differing byte.
+fn digests_match(actual: &[u8], expected: &[u8]) -> bool {
+    actual
+        .iter()
+        .zip(expected)
+        .fold(expected.len() ^ actual.len(), |acc, (a, b)| {
+            acc | usize::from(a ^ b)
+        })
+        == 0
+}
+
+fn verifier_digest(salt: &[u8], key: &[u8]) -> [u8; 32] {
+    let mut digest = [0u8; 32];
+    HMAC_SHA256(salt, key, &mut digest);
+    digest
+}
+
 impl Deref for Stronghold {
     type Target = iota_stronghold::Stronghold;
     fn deref(&self) -> &Self::Target {
         &self.inner
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use std::{cell::Cell, io::ErrorKind};
+
+    use super::*;
+
+    /// A snapshot at a path that doesn't exist, never saved by these tests.
+    fn unsaved_stronghold() -> Stronghold {
+        let path = std::env::temp_dir().join(format!(
+            "stronghold-test-{}.hold",
+            hex::encode(rand::random::<[u8; 16]>())
+        ));
+        Stronghold::new(path, vec![7; 32]).unwrap()
+    }
+
+    #[test]
+    fn write_during_a_save_keeps_the_snapshot_dirty() {
+        let stronghold = unsaved_stronghold();
+        stronghold.mark_dirty();
+        stronghold
+            .commit_clean(|| {
+                stronghold.mark_dirty();
+                Ok(())
+            })
+            .unwrap();
+        assert!(stronghold.dirty_since().is_some());
+    }
+
+    #[test]
+    fn failed_save_keeps_the_snapshot_dirty() {
+        let stronghold = unsaved_stronghold();
+        stronghold.mark_dirty();
+        let since = stronghold.dirty_since();
+        assert!(stronghold.commit_clean(|| Err(Error::Corrupted)).is_err());
+        assert_eq!(stronghold.dirty_since(), since);
+
+        stronghold.commit_clean(|| Ok(())).unwrap();
+        assert_eq!(stronghold.dirty_since(), None);
+    }
+
+    fn runtime_error() -> Error {
+        iota_stronghold::ClientError::ClientDataNotPresent.into()
+    }
+
+    #[test]
+    fn in_procedure_names_runtime_errors_only() {
+        let e = runtime_error().in_procedure("AleoSign");
+        assert!(matches!(
+            &e,
+            Error::ProcedureFailed {
+                procedure: "AleoSign",
+                ..
+            }
+        ));
+        assert!(e.to_string().starts_with("`AleoSign` failed: "));
+
+        let e = Error::invalid_input("fee", "too low").in_procedure("AleoSign");
+        assert!(matches!(e, Error::InvalidInput { .. }));
+        assert!(matches!(
+            Error::Corrupted.in_procedure("AleoSign"),
+            Error::Corrupted
+        ));
+    }
+
+    #[test]
+    fn root_cause_skips_the_context() {
+        let e = runtime_error()
+            .in_procedure("AleoSign")
+            .with_snapshot("save", Path::new("vault.hold"));
+        assert!(matches!(
+            e.root_cause(),
+            Error::Stronghold(iota_stronghold::ClientError::ClientDataNotPresent)
+        ));
+        assert!(matches!(Error::Corrupted.root_cause(), Error::Corrupted));
+    }
+
+    #[test]
+    fn snapshot_error_names_the_operation_and_path() {
+        let e = Error::Corrupted.with_snapshot("load", Path::new("dir/vault.hold"));
+        assert_eq!(
+            e.to_string(),
+            format!(
+                "failed to load {}: snapshot file is corrupted",
+                Path::new("dir/vault.hold").display()
+            )
+        );
+    }
+
+    #[test]
+    fn io_error_kinds_are_classified() {
+        for kind in [
+            ErrorKind::Interrupted,
+            ErrorKind::WouldBlock,
+            ErrorKind::TimedOut,
+            ErrorKind::WriteZero,
+        ] {
+            assert!(is_transient(kind), "{kind:?}");
+        }
+        for kind in [
+            ErrorKind::NotFound,
+            ErrorKind::PermissionDenied,
+            ErrorKind::AlreadyExists,
+            ErrorKind::InvalidData,
+        ] {
+            assert!(!is_transient(kind), "{kind:?}");
+        }
+    }
+
+    #[test]
+    fn retry_transient_gives_up_after_save_attempts() {
+        let calls = Cell::new(0);
+        let e = retry_transient(|| {
+            calls.set(calls.get() + 1);
+            Err(std::io::Error::from(ErrorKind::Interrupted).into())
+        })
+        .unwrap_err();
+        assert!(matches!(e, Error::IoTransient(_)));
+        assert_eq!(calls.get(), SAVE_ATTEMPTS);
+    }
+
+    #[test]
+    fn retry_transient_does_not_retry_permanent_errors() {
+        let calls = Cell::new(0);
+        let e = retry_transient(|| {
+            calls.set(calls.get() + 1);
+            Err(std::io::Error::from(ErrorKind::PermissionDenied).into())
+        })
+        .unwrap_err();
+        assert!(matches!(e, Error::IoPermanent(_)));
+        assert_eq!(calls.get(), 1);
+
+        let calls = Cell::new(0);
+        let e = retry_transient(|| {
+            calls.set(calls.get() + 1);
+            Err(Error::Corrupted)
+        })
+        .unwrap_err();
+        assert!(matches!(e, Error::Corrupted));
+        assert_eq!(calls.get(), 1);
+    }
+
+    #[test]
+    fn retry_transient_returns_once_a_retry_succeeds() {
+        let calls = Cell::new(0);
+        retry_transient(|| {
+            calls.set(calls.get() + 1);
+            if calls.get() == 1 {
+                Err(std::io::Error::from(ErrorKind::TimedOut).into())
+            } else {
+                Ok(())
+            }
+        })
+        .unwrap();
+        assert_eq!(calls.get(), 2);
+    }
+
+    const SECRET: &str = "record1secretplaintext";
+
+    /// Checks that neither the display, the serialization nor the debug output of `e` quote
+    /// [`SECRET`].
+    fn assert_redacted(e: &Error) {
+        assert!(!e.to_string().contains(SECRET), "{e}");
+        assert!(!serde_json::to_string(e).unwrap().contains(SECRET));
+        assert!(!format!("{e:?}").contains(SECRET));
+    }
+
+    #[test]
+    fn invalid_value_is_redacted() {
+        let e = Error::invalid_value("record", format!("failed to parse `{SECRET}`"));
+        assert_redacted(&e);
+        assert_eq!(e.to_string(), "invalid record: the value is malformed");
+    }
+
+    #[test]
+    fn codec_is_redacted() {
+        let e = Error::Codec(Redacted::new(SECRET));
+        assert_redacted(&e);
+    }
+
+    #[test]
+    fn json_is_redacted() {
+        let e: Error = serde_json::from_str::<u64>(&format!("\"{SECRET}\""))
+            .unwrap_err()
+            .into();
+        assert_redacted(&e);
+        assert!(e.to_string().starts_with("malformed JSON"));
+    }
+
+    #[test]
+    fn mnemonic_is_redacted() {
+        let mnemonic =
+            crypto::keys::bip39::Mnemonic::from(format!("{} {SECRET}", ["abandon"; 11].join(" ")));
+        let e = Error::Mnemonic(
+            crypto::keys::bip39::wordlist::verify(
+                &mnemonic,
+                &crypto::keys::bip39::wordlist::ENGLISH,
+            )
+            .unwrap_err(),
+        );
+        assert_redacted(&e);
+    }
+
+    #[test]
+    fn redacted_debug_hides_the_detail() {
+        let detail = Redacted::new(SECRET);
+        assert_eq!(format!("{detail}"), "<redacted>");
+        assert_eq!(format!("{detail:?}"), "Redacted(..)");
+    }
+
+    #[test]
+    fn context_keeps_the_redaction() {
+        let e =
+            Error::invalid_value("record", SECRET).with_snapshot("save", Path::new("vault.hold"));
+        assert_redacted(&Error::batch_procedure(0, e));
+    }
+
+    #[cfg(feature = "error-detail")]
+    #[test]
+    fn debug_detail_keeps_the_secret() {
+        let e =
+            Error::invalid_value("record", SECRET).with_snapshot("save", Path::new("vault.hold"));
+        assert_eq!(e.debug_detail().as_deref(), Some(SECRET));
+    }
+}
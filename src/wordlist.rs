@@ -0,0 +1,142 @@
+// Copyright 2019-2023 Tauri Programme within The Commons Conservancy
+// SPDX-License-Identifier: Apache-2.0
+// SPDX-License-Identifier: MIT
+
+use std::collections::HashSet;
+
+use crypto::keys::bip39::{
+    wordlist::{self, Wordlist},
+    Mnemonic, MnemonicRef,
+};
+use zeroize::Zeroizing;
+
+use crate::{
+    output::{InvalidWord, MnemonicValidation},
+    stronghold::{Error, Result},
+};
+
+/// Number of words a BIP39 wordlist must contain.
+pub const WORDLIST_LENGTH: usize = 2048;
+
+/// Entropy used when generating a mnemonic, matching the built-in `BIP39Generate` (24 words).
+const ENTROPY_LENGTH: usize = 32;
+
+/// Validates a caller supplied wordlist and runs `f` with it.
+///
+/// The list must contain exactly [`WORDLIST_LENGTH`] words and no duplicates.
+pub fn with_wordlist<T>(words: &[String], f: impl FnOnce(&Wordlist) -> Result<T>) -> Result<T> {
+    let words: Vec<&str> = words.iter().map(String::as_str).collect();
+    let words: &[&str; WORDLIST_LENGTH] = words.as_slice().try_into().map_err(|_| {
+        Error::InvalidWordlist(format!(
+            "expected {WORDLIST_LENGTH} words, got {}",
+            words.len()
+        ))
+    })?;
+
+    let mut seen = HashSet::with_capacity(WORDLIST_LENGTH);
+    if let Some(duplicate) = words.iter().find(|word| !seen.insert(**word)) {
+        return Err(Error::InvalidWordlist(format!(
+            "duplicate word `{duplicate}`"
+        )));
+    }
+
+    let wordlist =
+        Wordlist::new(" ", words).map_err(|e| Error::InvalidWordlist(format!("{e:?}")))?;
+    f(&wordlist)
+}
+
+/// Checks that `mnemonic` is a valid phrase for the custom `words`.
+pub fn verify(mnemonic: &MnemonicRef, words: &[String]) -> Result<()> {
+    with_wordlist(words, |wordlist| {
+        wordlist::verify(mnemonic, wordlist).map_err(Error::Mnemonic)
+    })
+}
+
+/// Checks `phrase` against the built-in wordlist of `language`, English by default.
+///
+/// Only fails if `language` is unknown; an invalid phrase is reported in the outcome.
+pub fn validate(phrase: &str, language: Option<&str>) -> Result<MnemonicValidation> {
+    let wordlist = match language.map(str::to_ascii_lowercase).as_deref() {
+        None | Some("english") => &wordlist::ENGLISH,
+        Some(_) => {
+            return Err(Error::invalid_input(
+                "language",
+                "only `english` is built in",
+            ))
+        }
+    };
+    let mnemonic = Mnemonic::from(phrase.to_string());
+    let (invalid_word, reason) = match wordlist::verify(&mnemonic, wordlist) {
+        Ok(()) => (None, None),
+        Err(wordlist::Error::NoSuchWord(word)) => {
+            // The word is reported NFKD normalized, like the phrase held by `mnemonic`.
+            let normalized = AsRef::<str>::as_ref(&*mnemonic);
+            let index = normalized
+                .split_whitespace()
+                .position(|candidate| candidate == word);
+            let reason = match index {
+                Some(index) => format!("word {} is not in the wordlist", index + 1),
+                None => "a word is not in the wordlist".into(),
+            };
+            (Some(InvalidWord { index, word }), Some(reason))
+        }
+        Err(wordlist::Error::ChecksumMismatch) => (
+            None,
+            Some("the checksum does not match, a word is wrong or out of place".into()),
+        ),
+        Err(wordlist::Error::InvalidEntropyCount(_)) => (
+            None,
+            Some("a mnemonic has 12, 15, 18, 21 or 24 words".into()),
+        ),
+        Err(e) => (None, Some(format!("{e:?}"))),
+    };
+    Ok(MnemonicValidation {
+        valid: reason.is_none(),
+        invalid_word,
+        reason,
+    })
+}
+
+/// Generates a fresh mnemonic encoded with the custom `words`.
+pub fn generate(words: &[String]) -> Result<Mnemonic> {
+    with_wordlist(words, |wordlist| {
+        let mut entropy = Zeroizing::new([0u8; ENTROPY_LENGTH]);
+        crypto::utils::rand::fill(entropy.as_mut())?;
+        wordlist::encode(entropy.as_ref(), wordlist).map_err(Error::Mnemonic)
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn phrase_with(word: &str, index: usize) -> String {
+        let mut words = vec!["abandon"; 12];
+        words[index] = word;
+        words.join(" ")
+    }
+
+    #[test]
+    fn validate_names_the_unknown_word() {
+        let validation = validate(&phrase_with("abandn", 4), None).unwrap();
+        assert!(!validation.valid);
+        assert_eq!(validation.invalid_word.unwrap().index, Some(4));
+        assert_eq!(validation.reason.unwrap(), "word 5 is not in the wordlist");
+    }
+
+    #[test]
+    fn validate_finds_non_ascii_words() {
+        // Composed, while the wordlist reports the word decomposed.
+        let validation = validate(&phrase_with("caf\u{e9}", 7), None).unwrap();
+        assert_eq!(validation.invalid_word.unwrap().index, Some(7));
+    }
+
+    #[test]
+    fn validate_accepts_a_valid_phrase() {
+        let phrase = "abandon abandon abandon abandon abandon abandon abandon abandon \
+                      abandon abandon abandon about";
+        let validation = validate(phrase, Some("English")).unwrap();
+        assert!(validation.valid);
+        assert_eq!(validation.invalid_word, None);
+    }
+}
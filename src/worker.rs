@@ -0,0 +1,382 @@
+// Copyright 2019-2023 Tauri Programme within The Commons Conservancy
+// SPDX-License-Identifier: Apache-2.0
+// SPDX-License-Identifier: MIT
+
+//! Keeping the decrypted snapshots in a separate worker process, so that a compromised
+//! webview reading the memory of the app process finds no keys there.
+//!
+//! The worker is any executable calling [`serve_stdio`], typically the app itself started
+//! with a dedicated argument:
+//!
+//! ```ignore
+//! if std::env::args().any(|arg| arg == "--stronghold-worker") {
+//!     let collection = StrongholdCollection::default();
+//!     return worker::serve_stdio::<MainnetV0>(&collection, || hash_function());
+//! }
+//! let worker = Worker::spawn(Command::new(std::env::current_exe()?).arg("--stronghold-worker"))?;
+//! ```
+//!
+//! The app then calls the methods of [`Worker`] instead of the functions of the crate. They
+//! are sent over the standard input and output of the worker as JSON lines, one
+//! [`WorkerRequest`] answered by one [`WorkerResponse`]. The password hash function runs in
+//! the worker, so the key never reaches the app. Passwords, store values and procedure
+//! outputs do cross the pipe, and the serialized copies are wiped on a best-effort basis.
+//!
+//! Only the functions listed in [`WorkerRequest`] are proxied; the others still need an
+//! in-process [`StrongholdCollection`].
+
+use std::{
+    io::{self, BufRead, BufReader, Write},
+    process::{Child, ChildStdin, ChildStdout, Command, Stdio},
+    sync::Mutex,
+    time::Duration,
+};
+
+use serde::{de::DeserializeOwned, Deserialize, Serialize};
+use snarkvm_console::network::Network;
+use zeroize::Zeroizing;
+
+use crate::{
+    blocking,
+    password::Password,
+    paths::{ClientPath, SnapshotPath},
+    secret::SecretString,
+    stronghold::{Error, Result},
+    PasswordHashFunction, ProcedureDto, StrongholdCollection,
+};
+
+/// A call proxied to the worker, named after the crate function it runs.
+#[derive(Deserialize, Serialize, Debug)]
+#[serde(tag = "op", content = "args", rename_all = "camelCase")]
+pub enum WorkerRequest {
+    Initialize {
+        snapshot_path: SnapshotPath,
+        password: SecretString,
+    },
+    Save {
+        snapshot_path: SnapshotPath,
+    },
+    Destroy {
+        snapshot_path: SnapshotPath,
+    },
+    CreateClient {
+        snapshot_path: SnapshotPath,
+        client: ClientPath,
+    },
+    LoadClient {
+        snapshot_path: SnapshotPath,
+        client: ClientPath,
+    },
+    GetStoreRecord {
+        snapshot_path: SnapshotPath,
+        client: ClientPath,
+        key: String,
+    },
+    SaveStoreRecord {
+        snapshot_path: SnapshotPath,
+        client: ClientPath,
+        key: String,
+        value: Vec<u8>,
+        lifetime: Option<Duration>,
+    },
+    RemoveStoreRecord {
+        snapshot_path: SnapshotPath,
+        client: ClientPath,
+        key: String,
+    },
+    /// The procedure in its JSON form, deserialized into a [`ProcedureDto`] by the worker.
+    ExecuteProcedure {
+        snapshot_path: SnapshotPath,
+        client: ClientPath,
+        procedure: serde_json::Value,
+    },
+    /// Saves and unloads every snapshot, then stops the worker.
+    Shutdown,
+}
+
+/// The outcome of a [`WorkerRequest`]: the value returned by the function, or its error
+/// message.
+#[derive(Deserialize, Serialize, Debug)]
+#[serde(tag = "status", content = "value", rename_all = "camelCase")]
+pub enum WorkerResponse {
+    Ok(serde_json::Value),
+    Err(String),
+}
+
+impl<T: Serialize> From<Result<T>> for WorkerResponse {
+    fn from(result: Result<T>) -> Self {
+        match result.and_then(|value| serde_json::to_value(value).map_err(Into::into)) {
+            Ok(value) => Self::Ok(value),
+            Err(e) => Self::Err(e.to_string()),
+        }
+    }
+}
+
+/// Serves the requests of a [`Worker`] on the standard input and output of the process.
+pub fn serve_stdio<N: Network>(
+    collection: &StrongholdCollection,
+    hash_function: impl Fn() -> PasswordHashFunction,
+) -> Result<()> {
+    serve::<N>(
+        collection,
+        hash_function,
+        io::stdin().lock(),
+        io::stdout().lock(),
+    )
+}
+
+/// Serves the requests read from `input`, writing a response for each to `output`, until a
+/// [`WorkerRequest::Shutdown`] or the end of `input`.
+///
+/// `hash_function` builds the hash function used for every [`WorkerRequest::Initialize`].
+/// Every snapshot is saved and unloaded before returning, also when `input` is closed
+/// because the app went away.
+pub fn serve<N: Network>(
+    collection: &StrongholdCollection,
+    hash_function: impl Fn() -> PasswordHashFunction,
+    mut input: impl BufRead,
+    mut output: impl Write,
+) -> Result<()> {
+    loop {
+        let mut line = Zeroizing::new(String::new());
+        if input.read_line(&mut line)? == 0 {
+            return blocking::shutdown(collection);
+        }
+        let (response, stop) = match serde_json::from_str(&line) {
+            Ok(WorkerRequest::Shutdown) => (blocking::shutdown(collection).into(), true),
+            Ok(request) => (handle::<N>(collection, &hash_function, request), false),
+            Err(e) => (WorkerResponse::Err(Error::from(e).to_string()), false),
+        };
+        let mut response = Zeroizing::new(serde_json::to_vec(&response)?);
+        response.push(b'\n');
+        output.write_all(&response)?;
+        output.flush()?;
+        if stop {
+            return Ok(());
+        }
+    }
+}
+
+fn handle<N: Network>(
+    collection: &StrongholdCollection,
+    hash_function: &impl Fn() -> PasswordHashFunction,
+    request: WorkerRequest,
+) -> WorkerResponse {
+    match request {
+        WorkerRequest::Initialize {
+            snapshot_path,
+            password,
+        } => blocking::initialize(
+            collection,
+            hash_function(),
+            snapshot_path,
+            password.into_inner(),
+        )
+        .into(),
+        WorkerRequest::Save { snapshot_path } => blocking::save(collection, snapshot_path).into(),
+        WorkerRequest::Destroy { snapshot_path } => {
+            blocking::destroy(collection, snapshot_path).into()
+        }
+        WorkerRequest::CreateClient {
+            snapshot_path,
+            client,
+        } => blocking::create_client(collection, snapshot_path, client).into(),
+        WorkerRequest::LoadClient {
+            snapshot_path,
+            client,
+        } => blocking::load_client(collection, snapshot_path, client).into(),
+        WorkerRequest::GetStoreRecord {
+            snapshot_path,
+            client,
+            key,
+        } => blocking::get_store_record(collection, snapshot_path, client, key).into(),
+        WorkerRequest::SaveStoreRecord {
+            snapshot_path,
+            client,
+            key,
+            value,
+            lifetime,
+        } => blocking::save_store_record(collection, snapshot_path, client, key, value, lifetime)
+            .into(),
+        WorkerRequest::RemoveStoreRecord {
+            snapshot_path,
+            client,
+            key,
+        } => blocking::remove_store_record(collection, snapshot_path, client, key).into(),
+        WorkerRequest::ExecuteProcedure {
+            snapshot_path,
+            client,
+            procedure,
+        } => serde_json::from_value::<ProcedureDto<N>>(procedure)
+            .map_err(Into::into)
+            .and_then(|procedure| {
+                blocking::execute_procedure(collection, snapshot_path, client, procedure)
+            })
+            .into(),
+        WorkerRequest::Shutdown => blocking::shutdown(collection).into(),
+    }
+}
+
+struct Channel {
+    stdin: ChildStdin,
+    stdout: BufReader<ChildStdout>,
+}
+
+/// The app side of a worker process running [`serve_stdio`].
+///
+/// Requests are sent one at a time, so a slow procedure holds the others back. Dropping the
+/// worker closes its input, upon which it saves every snapshot and exits.
+pub struct Worker {
+    child: Child,
+    channel: Mutex<Option<Channel>>,
+}
+
+impl Worker {
+    /// Starts `command` as the worker, taking its standard input and output over.
+    pub fn spawn(command: &mut Command) -> Result<Self> {
+        let mut child = command
+            .stdin(Stdio::piped())
+            .stdout(Stdio::piped())
+            .spawn()?;
+        let channel = Channel {
+            stdin: child.stdin.take().expect("stdin is piped"),
+            stdout: BufReader::new(child.stdout.take().expect("stdout is piped")),
+        };
+        Ok(Self {
+            child,
+            channel: Mutex::new(Some(channel)),
+        })
+    }
+
+    /// Sends `request` and waits for its response.
+    pub fn request<T: DeserializeOwned>(&self, request: &WorkerRequest) -> Result<T> {
+        let mut channel = self.channel.lock().unwrap();
+        let channel = channel
+            .as_mut()
+            .ok_or_else(|| Error::Worker("the worker was shut down".into()))?;
+        let mut line = Zeroizing::new(serde_json::to_vec(request)?);
+        line.push(b'\n');
+        channel.stdin.write_all(&line)?;
+        channel.stdin.flush()?;
+
+        let mut response = Zeroizing::new(String::new());
+        if channel.stdout.read_line(&mut response)? == 0 {
+            return Err(Error::Worker("the worker exited".into()));
+        }
+        match serde_json::from_str(&response)? {
+            WorkerResponse::Ok(value) => Ok(serde_json::from_value(value)?),
+            WorkerResponse::Err(message) => Err(Error::Worker(message)),
+        }
+    }
+
+    pub fn initialize(
+        &self,
+        snapshot_path: SnapshotPath,
+        password: impl Into<Password>,
+    ) -> Result<()> {
+        let password = password.into();
+        self.request(&WorkerRequest::Initialize {
+            snapshot_path,
+            password: password.as_str().into(),
+        })
+    }
+
+    pub fn save(&self, snapshot_path: SnapshotPath) -> Result<()> {
+        self.request(&WorkerRequest::Save { snapshot_path })
+    }
+
+    pub fn destroy(&self, snapshot_path: SnapshotPath) -> Result<()> {
+        self.request(&WorkerRequest::Destroy { snapshot_path })
+    }
+
+    pub fn create_client(&self, snapshot_path: SnapshotPath, client: ClientPath) -> Result<()> {
+        self.request(&WorkerRequest::CreateClient {
+            snapshot_path,
+            client,
+        })
+    }
+
+    pub fn load_client(&self, snapshot_path: SnapshotPath, client: ClientPath) -> Result<()> {
+        self.request(&WorkerRequest::LoadClient {
+            snapshot_path,
+            client,
+        })
+    }
+
+    pub fn get_store_record(
+        &self,
+        snapshot_path: SnapshotPath,
+        client: ClientPath,
+        key: String,
+    ) -> Result<Option<Vec<u8>>> {
+        self.request(&WorkerRequest::GetStoreRecord {
+            snapshot_path,
+            client,
+            key,
+        })
+    }
+
+    pub fn save_store_record(
+        &self,
+        snapshot_path: SnapshotPath,
+        client: ClientPath,
+        key: String,
+        value: Vec<u8>,
+        lifetime: Option<Duration>,
+    ) -> Result<Option<Vec<u8>>> {
+        self.request(&WorkerRequest::SaveStoreRecord {
+            snapshot_path,
+            client,
+            key,
+            value,
+            lifetime,
+        })
+    }
+
+    pub fn remove_store_record(
+        &self,
+        snapshot_path: SnapshotPath,
+        client: ClientPath,
+        key: String,
+    ) -> Result<Option<Vec<u8>>> {
+        self.request(&WorkerRequest::RemoveStoreRecord {
+            snapshot_path,
+            client,
+            key,
+        })
+    }
+
+    /// Executes the JSON form of a [`ProcedureDto`], as sent by the webview.
+    pub fn execute_procedure(
+        &self,
+        snapshot_path: SnapshotPath,
+        client: ClientPath,
+        procedure: serde_json::Value,
+    ) -> Result<Vec<u8>> {
+        self.request(&WorkerRequest::ExecuteProcedure {
+            snapshot_path,
+            client,
+            procedure,
+        })
+    }
+
+    /// Saves and unloads every snapshot in the worker, which then exits.
+    pub fn shutdown(&self) -> Result<()> {
+        let result = self.request(&WorkerRequest::Shutdown);
+        self.channel.lock().unwrap().take();
+        result
+    }
+}
+
+impl Drop for Worker {
+    fn drop(&mut self) {
+        // Closing its input makes the worker save and exit.
+        self.channel
+            .get_mut()
+            .unwrap_or_else(|e| e.into_inner())
+            .take();
+        if let Err(e) = self.child.wait() {
+            log::error!("failed to wait for the stronghold worker: {e}");
+        }
+    }
+}